@@ -1,9 +1,12 @@
 //! Module algogen Defines a trait for this.
 use crate::graphics::{DrawInstruction, ManyStepDrawable, SingleStepDrawable};
+use crate::observer::{Event, ObserverHandle};
 use crate::problems;
 use crate::problems::{GenericProblem, ManyStepProblem, SingleStepProblem, Solution};
-use rand::prelude::ThreadRng;
+use crate::trace::Trace;
+use crate::random::{derive, GenRng};
 use rulinalg::vector::Vector;
+use serde::{Deserialize, Serialize};
 
 /// Represents the starting size of the population in individuals.
 const POP_START: f64 = 25.0;
@@ -16,25 +19,91 @@ const DEATH_START: f64 = 20.0;
 /// All the non-killed people will have childs, this represents how much per couple.
 /// If there are 10 couples and 1.1 childpercouple the best couple will have 2 child.
 const CHILD_PER_COUPLE_START: f64 = 4.0;
+/// The population can not grow over this size.
+const MAX_POP: f64 = 200.0;
+/// At least this much childs are created every generation.
+const MIN_CHILDS: f64 = 4.0;
+/// Number of generations a supervisor can play.
+const MAX_STEP: usize = 20;
+/// The run is over after this number of evaluations.
+const MAX_EVALUATIONS: usize = 150;
+/// What an evaluation costs to the score of a run, as a part of its first best score.
+const EVALUATION_COST: f64 = 0.001;
 
-/// If you change the struct, change its size please.
-const PARAM_CHOICE_SIZE: usize = 6;
+/// The settings of a genetic algorithm, the defaults are the constants above.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlgoGenSettings {
+    pub pop_start: f64,
+    pub mut_start: f64,
+    pub elite_keep_start: f64,
+    pub death_start: f64,
+    pub child_per_couple_start: f64,
+    pub max_pop: f64,
+    pub min_childs: f64,
+    pub max_step: usize,
+    pub max_evaluations: usize,
+    /// Taken from the best score for every evaluation, as a part of the best score of the first
+    /// generation so that it means the same on every problem.
+    pub evaluation_cost: f64,
+}
+
+impl Default for AlgoGenSettings {
+    fn default() -> Self {
+        AlgoGenSettings {
+            pop_start: POP_START,
+            mut_start: MUT_START,
+            elite_keep_start: ELITE_KEEP_START,
+            death_start: DEATH_START,
+            child_per_couple_start: CHILD_PER_COUPLE_START,
+            max_pop: MAX_POP,
+            min_childs: MIN_CHILDS,
+            max_step: MAX_STEP,
+            max_evaluations: MAX_EVALUATIONS,
+            evaluation_cost: EVALUATION_COST,
+        }
+    }
+}
+
+/// What is needed to create a random genetic algorithm : the problem's config and the settings.
 #[derive(Debug, Clone)]
+pub struct AlgoGenConf<C> {
+    pub problem: C,
+    pub settings: AlgoGenSettings,
+}
+
+impl<C> AlgoGenConf<C> {
+    /// Uses the default settings.
+    pub fn new(problem: C) -> Self {
+        AlgoGenConf {
+            problem,
+            settings: AlgoGenSettings::default(),
+        }
+    }
+}
+
+/// If you change the struct, change its size please.
+pub const PARAM_CHOICE_SIZE: usize = 6;
+#[derive(Debug, Clone, PartialEq)]
 pub struct ParamChoice {
-    global: f64,
-    mutrate: f64,
-    elite: f64,
-    kills: f64,
-    birth_rate: f64,
+    pub global: f64,
+    pub mutrate: f64,
+    pub elite: f64,
+    pub kills: f64,
+    pub birth_rate: f64,
 }
 impl ParamChoice {
     pub fn new() -> Self {
+        Self::starting(&AlgoGenSettings::default())
+    }
+    /// The parameters at the start of a run.
+    pub fn starting(settings: &AlgoGenSettings) -> Self {
         ParamChoice {
             global: 1.0,
-            mutrate: MUT_START,
-            elite: ELITE_KEEP_START,
-            kills: DEATH_START,
-            birth_rate: CHILD_PER_COUPLE_START,
+            mutrate: settings.mut_start,
+            elite: settings.elite_keep_start,
+            kills: settings.death_start,
+            birth_rate: settings.child_per_couple_start,
         }
     }
     /// Everything stays the same.
@@ -70,9 +139,23 @@ impl ParamChoice {
             birth_rate: vec[4],
         }
     }
+    /// Returns the choice as the vector a network would output, the unused outputs are 0.5.
+    pub fn into_vector(self) -> Vector<f64> {
+        let mut ret = vec![0.5; PARAM_CHOICE_SIZE];
+        ret[0] = self.global;
+        ret[1] = self.mutrate;
+        ret[2] = self.elite;
+        ret[3] = self.kills;
+        ret[4] = self.birth_rate;
+        Vector::new(ret)
+    }
 }
 
-const GEN_RESULT_SIZE: usize = 15;
+pub const GEN_RESULT_SIZE: usize = 15;
+/// Version of the layout of the observations (GenResult::into_vector) and of the actions
+/// (ParamChoice::from_vector), saved networks with another version can not be used.
+/// Change it whenever one of them changes.
+pub const SCHEMA_VERSION: u32 = 1;
 #[derive(Debug, Clone)]
 pub struct GenResult {
     pub max: f64,
@@ -133,28 +216,45 @@ impl GenResult {
 /// Proceed as told, see the next_gen function.
 #[derive(Debug, Clone)]
 pub struct AlgoGen<P: SingleStepProblem> {
-    random: ThreadRng,
+    random: GenRng,
     pop: Vec<P::Sol>,
     params: ParamChoice,
     problem: P,
     last_res: GenResult,
     individuals_played: usize,
+    /// Improvement of the best score per evaluation during the last step.
+    last_reward: Option<f64>,
+    trace: Trace,
+    observer: ObserverHandle,
+    settings: AlgoGenSettings,
 }
 
 impl<P: SingleStepProblem> AlgoGen<P> {
-    pub fn initiate(prob_conf: P::ProblemConfig, my_rand: &mut ThreadRng) -> Self {
+    pub fn initiate(prob_conf: P::ProblemConfig, my_rand: &mut GenRng) -> Self {
+        Self::initiate_with(prob_conf, AlgoGenSettings::default(), my_rand)
+    }
+
+    pub fn initiate_with(
+        prob_conf: P::ProblemConfig,
+        settings: AlgoGenSettings,
+        my_rand: &mut GenRng,
+    ) -> Self {
         let mut random = my_rand;
         let prob = P::random(&mut random, &prob_conf);
-        let pop = (0..POP_START as usize)
+        let pop = (0..settings.pop_start as usize)
             .map(|_| P::Sol::random(&mut random, &prob.get_sol_conf()))
             .collect();
         AlgoGen {
             problem: prob,
-            random: random.clone(),
+            random: derive(random),
             pop: pop,
             last_res: GenResult::new(),
-            params: ParamChoice::new(),
+            params: ParamChoice::starting(&settings),
             individuals_played: 0,
+            last_reward: None,
+            trace: Trace::new(),
+            observer: ObserverHandle::silent(),
+            settings,
         }
     }
 
@@ -171,37 +271,73 @@ impl<P: SingleStepProblem> AlgoGen<P> {
     ///
     ///  - evaluate all solutions.
     ///  - sort them by score.
+    ///  - record the generation in the trace.
     ///  Returns the statistics.
     ///
     pub fn next_gen(&mut self, choice_next: ParamChoice) -> &GenResult {
-        self.apply_params(choice_next);
+        let choice = choice_next.clone();
+        let requested = self.apply_params(choice_next);
         self.kill_last();
         self.mutate_average();
         self.make_childs();
         self.sort_pop();
         self.update_res();
+        self.trace.push(
+            self.last_res.clone(),
+            choice,
+            requested,
+            self.params.clone(),
+            self.pop.len(),
+            self.individuals_played,
+        );
+        self.observer.notify(Event::GenerationFinished {
+            generation: self.trace.rows().len() - 1,
+            stats: self.last_res.clone(),
+            pop_size: self.pop.len(),
+            evaluations: self.individuals_played,
+        });
         &self.last_res
     }
 
+    /// Sets the observer receiving the events of this run, silent by default.
+    pub fn set_observer(&mut self, observer: ObserverHandle) {
+        self.observer = observer;
+    }
+
+    /// Returns the statistics of every generation played so far.
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
     /// Apply the params and does a little bit of sanity checks :
-    /// No pop > 200
-    /// No pop < 2
+    /// No pop > max_pop
+    /// No less than min_childs childs
     /// Params elite < pop+2
     /// It does so by modifying the birth rate and checking the kills/pop.
-    pub fn apply_params(&mut self, choice: ParamChoice) {
+    /// Returns the parameters asked for, before the checks.
+    pub fn apply_params(&mut self, choice: ParamChoice) -> ParamChoice {
         self.params.update(choice);
+        let requested = self.params.clone();
         if self.params.kills >= self.pop.len() as f64 - 2.0 {
             self.params.kills = self.pop.len() as f64 - 2.0;
         }
-        if (self.pop.len() as f64 - self.params.kills) * self.params.birth_rate > 200.0 {
-            self.params.birth_rate = 200.0 / (self.pop.len() as f64 - self.params.kills);
+        let survivors = self.pop.len() as f64 - self.params.kills;
+        if survivors * self.params.birth_rate > self.settings.max_pop {
+            self.params.birth_rate = self.settings.max_pop / survivors;
         }
-        if (self.pop.len() as f64 - self.params.kills) * self.params.birth_rate < 4.0 {
-            self.params.birth_rate = 4.0 / (self.pop.len() as f64 - self.params.kills);
+        if survivors * self.params.birth_rate < self.settings.min_childs {
+            self.params.birth_rate = self.settings.min_childs / survivors;
         }
         if (self.pop.len() as f64 - self.params.kills) <= self.params.elite {
             self.params.elite = (self.pop.len() as f64 - self.params.kills) - 1.0;
         }
+        if requested != self.params {
+            self.observer.notify(Event::ParamsClamped {
+                requested: requested.clone(),
+                applied: self.params.clone(),
+            });
+        }
+        requested
     }
 
     /// Updates the statistics.
@@ -245,7 +381,10 @@ impl<P: SingleStepProblem> AlgoGen<P> {
     /// Mutate the average performing individuals
     fn mutate_average(&mut self) {
         if self.pop.len() <= self.params.elite as usize {
-            println!("echec {:?}", self.params);
+            self.observer.notify(Event::EliteTooBig {
+                params: self.params.clone(),
+                pop_size: self.pop.len(),
+            });
         }
         let nb_average = self.pop.len() - self.params.elite as usize;
         for i in nb_average..self.pop.len() {
@@ -268,6 +407,11 @@ impl<P: SingleStepProblem> AlgoGen<P> {
             .unwrap()
     }
 
+    /// Returns the number of solutions evaluated so far.
+    pub fn evaluations(&self) -> usize {
+        self.individuals_played
+    }
+
     pub fn demonstrate(&self) {
         self.problem.demonstrate(self.pop.first().unwrap());
     }
@@ -305,7 +449,7 @@ impl<P: SingleStepProblem> SupervisableSolver<P> for AlgoGen<P> {
     }
 
     fn random(
-        xsr: &mut ThreadRng,
+        xsr: &mut GenRng,
         prob_conf: &<Self as SupervisableSolver<P>>::CreateParam,
     ) -> Self {
         Self::initiate(prob_conf, xsr)
@@ -313,20 +457,17 @@ impl<P: SingleStepProblem> SupervisableSolver<P> for AlgoGen<P> {
 }
 */
 impl<T: SingleStepProblem + Clone> GenericProblem for AlgoGen<T> {
-    type ProblemConfig = T::ProblemConfig;
+    type ProblemConfig = AlgoGenConf<T::ProblemConfig>;
 
-    fn random(xsr: &mut ThreadRng, prob_conf: &<Self as GenericProblem>::ProblemConfig) -> Self {
-        AlgoGen::<T>::initiate(prob_conf.clone(), xsr)
+    fn random(xsr: &mut GenRng, prob_conf: &<Self as GenericProblem>::ProblemConfig) -> Self {
+        AlgoGen::<T>::initiate_with(prob_conf.problem.clone(), prob_conf.settings.clone(), xsr)
     }
 
     fn print_state(&self) {
-        //self.demonstrate();
-        println!(
-            "best : {}\tmin : {}\t pop : {}\n",
-            self.last_res.max,
-            self.last_res.min,
-            self.pop.len()
-        );
+        self.observer.notify(Event::StateReported {
+            stats: self.last_res.clone(),
+            pop_size: self.pop.len(),
+        });
     }
 }
 
@@ -340,16 +481,24 @@ where
     }
 
     fn make_step(&mut self, choice: &Vector<f64>) {
+        let best = match self.trace.rows().is_empty() {
+            true => None,
+            false => Some(self.last_res.max),
+        };
+        let evaluations = self.individuals_played;
         self.next_gen(ParamChoice::from_vector(choice.clone()));
+        let spent = (self.individuals_played - evaluations).max(1) as f64;
+        self.last_reward = Some(best.map_or(0.0, |best| (self.last_res.max - best) / spent));
     }
 
     fn max_step(&self) -> Option<usize> {
-        Some(20)
+        Some(self.settings.max_step)
     }
 
     fn evaluate(&self) -> f64 {
-        //println!("{} in {} gives : {}", self.last_res.max, self.individuals_played, self.last_res.max.powf(2.0)/self.individuals_played as f64);
-        self.last_res.max.powf(2.0) / self.individuals_played as f64
+        // worse with every evaluation whatever the sign of the best score
+        let scale = self.trace.rows().first().map_or(0.0, |first| first.stats.max.abs());
+        self.last_res.max - self.settings.evaluation_cost * scale * self.individuals_played as f64
     }
 
     fn input_space(&self) -> usize {
@@ -361,7 +510,19 @@ where
     }
 
     fn is_solved(&self) -> bool {
-        self.individuals_played > 150
+        self.individuals_played > self.settings.max_evaluations
+    }
+
+    fn step_reward(&self) -> Option<f64> {
+        self.last_reward
+    }
+
+    fn set_observer(&mut self, observer: ObserverHandle) {
+        self.observer = observer;
+    }
+
+    fn reseed(&mut self, rng: GenRng) {
+        self.random = rng;
     }
 }
 
@@ -376,3 +537,10 @@ where
         a
     }
 }
+
+impl<T: SingleStepDrawable + Clone> AlgoGen<T> {
+    /// Draws the best solution of the population.
+    pub fn best_frames(&self) -> Vec<DrawInstruction> {
+        self.problem.get_frames(self.best())
+    }
+}
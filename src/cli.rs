@@ -0,0 +1,147 @@
+//! The cli module
+//! Parses the command line of the neugene binary.
+use crate::config::{Config, NetworkSettings};
+use crate::random::{self, GenRng};
+use crate::reilearn::TrainingScheme;
+
+pub const USAGE: &str = "usage : neugene <command> [options]
+
+commands :
+    train    trains a network and saves it
+    eval     compares a saved network against baselines on test problems
+    bench    trains a network with every training scheme on the same problems and compares them
+    demo     prints a genetic algorithm supervised by a network
+    gui      opens the window showing the learning
+
+options :
+    --config <path>        toml file with the settings of the run, see config.rs
+    --problem <spec>       a problem or a mixture like maze:20,lineareq:5,turnaround, the problems
+                           are maze, lineareq, walljump, turnaround, easy, easystep and all
+                           (default turnaround)
+    --size <n>             size of the problems without their own config (default 50)
+    --seed <n>             seed for the problems and the genetic algorithms
+    --generations <n>      number of learning generations for train and bench (default 10)
+    --hidden <n,n,...>     sizes of the hidden layers for train (default 40,10)
+    --scheme <name>        how the network learns : elite_imitation, reinforce, evolution or
+                           branching (default elite_imitation)
+    --net <path>           network to load for eval and demo
+    --out <path>           where train saves the network (default network.txt)
+    --trace <path>         where demo saves the trace, as csv or jsonl depending on the extension
+    --render <path>        where train and demo save the frames of a supervised run : an animated
+                           .gif, or one .png or .svg per frame, or term to play them in the terminal";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Train,
+    Eval,
+    Bench,
+    Demo,
+    Gui,
+}
+
+/// The options, the ones that are in the config override the config's values when given.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub config: Option<String>,
+    pub problem: Option<String>,
+    pub size: Option<usize>,
+    pub seed: Option<u64>,
+    pub generations: Option<usize>,
+    pub hidden: Option<Vec<usize>>,
+    pub scheme: Option<TrainingScheme>,
+    pub net: Option<String>,
+    pub out: String,
+    pub trace: Option<String>,
+    pub render: Option<String>,
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Options {
+            config: None,
+            problem: None,
+            size: None,
+            seed: None,
+            generations: None,
+            hidden: None,
+            scheme: None,
+            net: None,
+            out: String::from("network.txt"),
+            trace: None,
+            render: None,
+        }
+    }
+
+    /// Loads the config file if any and applies the options given on the command line.
+    pub fn resolve_config(&self) -> Result<Config, String> {
+        let mut config = match self.config {
+            Some(ref path) => Config::load(path)?,
+            None => Config::default(),
+        };
+        if let Some(ref problem) = self.problem {
+            config.problem.name = problem.clone();
+        }
+        if let Some(size) = self.size {
+            config.problem.size = size;
+        }
+        if let Some(generations) = self.generations {
+            config.learning.generations = generations;
+        }
+        if let Some(ref hidden) = self.hidden {
+            config.network.hidden = hidden.clone();
+        }
+        if let Some(scheme) = self.scheme {
+            config.learning.scheme = scheme;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Returns the generator to use, seeded if a seed was given.
+    pub fn rng(&self) -> GenRng {
+        match self.seed {
+            Some(seed) => random::seeded(seed),
+            None => random::from_entropy(),
+        }
+    }
+}
+
+/// Parses the arguments (without the program's name).
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<(Command, Options), String> {
+    let command = match args.next().as_ref().map(|s| s.as_str()) {
+        Some("train") => Command::Train,
+        Some("eval") => Command::Eval,
+        Some("bench") => Command::Bench,
+        Some("demo") => Command::Demo,
+        Some("gui") | None => Command::Gui,
+        Some(other) => return Err(format!("unknown command : {}", other)),
+    };
+    let mut options = Options::new();
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {}", flag))?;
+        match flag.as_str() {
+            "--config" => options.config = Some(value),
+            "--problem" => options.problem = Some(value),
+            "--size" => options.size = Some(parse_value(&flag, &value)?),
+            "--seed" => options.seed = Some(parse_value(&flag, &value)?),
+            "--generations" => options.generations = Some(parse_value(&flag, &value)?),
+            "--hidden" => options.hidden = Some(NetworkSettings::parse_hidden(&value)?),
+            "--scheme" => options.scheme = Some(parse_value(&flag, &value)?),
+            "--net" => options.net = Some(value),
+            "--out" => options.out = value,
+            "--trace" => options.trace = Some(value),
+            "--render" => options.render = Some(value),
+            _ => return Err(format!("unknown option : {}", flag)),
+        }
+    }
+    Ok((command, options))
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse::<T>()
+        .map_err(|_| format!("invalid value for {} : {}", flag, value))
+}
@@ -0,0 +1,473 @@
+//! The config module
+//! Everything that can be tuned for a run, loaded from a toml file.
+//! Every value left out of the file takes the default from params.rs and algogen.rs.
+//!
+//! ```toml
+//! [problem]
+//! name = "maze"
+//! size = 20
+//!
+//! [learning]
+//! nb_problems = 50
+//! percent_elite = 0.1
+//!
+//! [network]
+//! hidden = [30, 10]
+//! ```
+use crate::algogen::{AlgoGenSettings, GEN_RESULT_SIZE, PARAM_CHOICE_SIZE};
+use crate::history::HistorySettings;
+use crate::params::*;
+use crate::reilearn::{LearnParams, Normalization, TrainingScheme};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// The problem the supervisor learns on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProblemSettings {
+    /// A registered problem or a mixture : "maze:20,lineareq:5,turnaround".
+    pub name: String,
+    /// The config of the problems that do not have their own.
+    pub size: usize,
+}
+
+impl Default for ProblemSettings {
+    fn default() -> Self {
+        ProblemSettings {
+            name: String::from("turnaround"),
+            size: PROB_CONF_SIZE,
+        }
+    }
+}
+
+/// The layout of the supervisor network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkSettings {
+    pub hidden: Vec<usize>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        NetworkSettings {
+            hidden: HIDDEN_LAYERS.to_vec(),
+        }
+    }
+}
+
+impl NetworkSettings {
+    /// Parses the sizes of the hidden layers : "40,10".
+    pub fn parse_hidden(s: &str) -> Result<Vec<usize>, String> {
+        s.split(',')
+            .map(|v| {
+                v.trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid hidden layer size : {}", v))
+            })
+            .collect()
+    }
+
+    /// All the layers, from the genetic algorithm's statistics and what the network sees of the
+    /// previous steps to the parameter choice and its memory.
+    pub fn layers(&self, history: &HistorySettings) -> Vec<usize> {
+        let mut layers = vec![history.input_size(GEN_RESULT_SIZE, PARAM_CHOICE_SIZE)];
+        layers.extend(self.hidden.iter());
+        layers.push(history.output_size(PARAM_CHOICE_SIZE));
+        layers
+    }
+}
+
+/// The size of the drawing area, the charts are drawn on its right.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowSettings {
+    pub width: i32,
+    pub height: i32,
+    pub chart_width: i32,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        WindowSettings {
+            width: WIDTH,
+            height: HEIGHT,
+            chart_width: CHART_WIDTH,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub problem: ProblemSettings,
+    pub learning: LearnParams,
+    pub algogen: AlgoGenSettings,
+    pub network: NetworkSettings,
+    pub window: WindowSettings,
+}
+
+impl Config {
+    /// Reads and validates the config file.
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self, String> {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("could not read {} : {}", path.as_ref().display(), e))?;
+        Self::parse(&content)
+            .map_err(|e| format!("invalid config {} : {}", path.as_ref().display(), e))
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(content).map_err(|e| e.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The config as it was used, with every default filled in.
+    pub fn to_toml(&self) -> Result<String, String> {
+        toml::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Writes the config as it was used, with every default filled in.
+    pub fn save<T: AsRef<Path>>(&self, path: T) -> Result<(), String> {
+        let content = self.to_toml()?;
+        fs::write(&path, content)
+            .map_err(|e| format!("could not write {} : {}", path.as_ref().display(), e))
+    }
+
+    /// Writes the config next to an output of the run : out.txt gives out.config.toml
+    pub fn save_next_to<T: AsRef<Path>>(&self, output: T) -> Result<(), String> {
+        self.save(output.as_ref().with_extension("config.toml"))
+    }
+
+    /// Checks every value and returns all the problems found.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = vec![];
+        let mut check = |ok: bool, msg: String| {
+            if !ok {
+                errors.push(msg)
+            }
+        };
+        let l = &self.learning;
+        check(
+            self.problem.size > 0,
+            String::from("problem.size must be positive"),
+        );
+        check(
+            l.nb_problems > 0,
+            String::from("learning.nb_problems must be positive"),
+        );
+        check(
+            l.test_per_prob >= 2,
+            format!(
+                "learning.test_per_prob must be at least 2 to compare playouts, got {}",
+                l.test_per_prob
+            ),
+        );
+        check(
+            l.percent_elite > 0.0 && l.percent_elite <= 0.5,
+            format!(
+                "learning.percent_elite must be in ]0, 0.5], got {}",
+                l.percent_elite
+            ),
+        );
+        check(
+            l.starting_coef >= 0.0,
+            format!(
+                "learning.starting_coef must not be negative, got {}",
+                l.starting_coef
+            ),
+        );
+        check(
+            l.coef_mod > 0.0 && l.coef_mod <= 1.0,
+            format!("learning.coef_mod must be in ]0, 1], got {}", l.coef_mod),
+        );
+        check(
+            l.test_data_size > 0,
+            String::from("learning.test_data_size must be positive"),
+        );
+        check(
+            l.validation_data_size > 0 && l.patience > 0,
+            String::from("learning.validation_data_size and learning.patience must be positive"),
+        );
+        check(
+            l.max_steps > 0,
+            String::from("learning.max_steps must be positive"),
+        );
+        check(
+            l.grad_step > 0.0,
+            format!("learning.grad_step must be positive, got {}", l.grad_step),
+        );
+        check(
+            l.grad_batch_size > 0 && l.lvbm_batch_size > 0,
+            String::from("learning.grad_batch_size and learning.lvbm_batch_size must be positive"),
+        );
+        check(
+            l.policy_step > 0.0,
+            format!("learning.policy_step must be positive, got {}", l.policy_step),
+        );
+        check(
+            l.baseline_rate > 0.0 && l.baseline_rate < 2.0,
+            format!("learning.baseline_rate must be in ]0, 2[, got {}", l.baseline_rate),
+        );
+        check(
+            l.es_population > 0 && l.es_problems > 0,
+            String::from("learning.es_population and learning.es_problems must be positive"),
+        );
+        check(
+            l.es_sigma > 0.0 && l.es_step > 0.0,
+            format!(
+                "learning.es_sigma and learning.es_step must be positive, got {} and {}",
+                l.es_sigma, l.es_step
+            ),
+        );
+        check(
+            l.gamma >= 0.0 && l.gamma <= 1.0,
+            format!("learning.gamma must be in [0, 1], got {}", l.gamma),
+        );
+        check(
+            l.branch_factor >= 2 && l.branch_horizon >= 1,
+            format!(
+                "learning.branch_factor must be at least 2 and learning.branch_horizon at least 1, \
+                 got {} and {}",
+                l.branch_factor, l.branch_horizon
+            ),
+        );
+        check(
+            l.normalization != Normalization::Rank || l.rank_baselines >= 2,
+            format!(
+                "learning.rank_baselines must be at least 2 to rank the scores, got {}",
+                l.rank_baselines
+            ),
+        );
+        check(
+            l.curriculum.iter().all(|&f| f > 0.0 && f <= 1.0)
+                && l.curriculum.windows(2).all(|w| w[0] < w[1]),
+            format!(
+                "learning.curriculum must be increasing fractions in ]0, 1], got {:?}",
+                l.curriculum
+            ),
+        );
+        check(
+            l.curriculum_thresholds.len() + 1 >= l.curriculum.len(),
+            format!(
+                "learning.curriculum_thresholds needs a threshold for each of the {} first levels",
+                l.curriculum.len().saturating_sub(1)
+            ),
+        );
+        check(
+            l.history.memory == 0 || l.scheme == TrainingScheme::Evolution,
+            format!(
+                "learning.history.memory needs the evolution scheme, the other ones have no \
+                 target for it, got {} with {:?}",
+                l.history.memory, l.scheme
+            ),
+        );
+        check(
+            l.replay.ratio >= 0.0 && l.replay.ratio < 1.0,
+            format!("learning.replay.ratio must be in [0, 1[, got {}", l.replay.ratio),
+        );
+        check(
+            l.replay.priority >= 0.0,
+            format!("learning.replay.priority must not be negative, got {}", l.replay.priority),
+        );
+        let e = &l.exploration;
+        check(
+            e.scales.iter().all(|&s| s >= 0.0),
+            format!("learning.exploration.scales must not be negative, got {:?}", e.scales),
+        );
+        check(
+            e.ou_theta > 0.0 && e.ou_theta <= 1.0,
+            format!("learning.exploration.ou_theta must be in ]0, 1], got {}", e.ou_theta),
+        );
+        check(
+            e.coef_step >= 0.0 && e.coef_min >= 0.0,
+            String::from("learning.exploration.coef_step and coef_min must not be negative"),
+        );
+        let a = &self.algogen;
+        check(
+            a.pop_start >= 4.0,
+            format!("algogen.pop_start must be at least 4, got {}", a.pop_start),
+        );
+        check(
+            a.death_start < a.pop_start - 2.0,
+            format!(
+                "algogen.death_start must leave at least 2 survivors out of {}, got {}",
+                a.pop_start, a.death_start
+            ),
+        );
+        check(
+            a.mut_start >= 0.0 && a.elite_keep_start >= 0.0 && a.child_per_couple_start > 0.0,
+            String::from("algogen starting parameters must not be negative"),
+        );
+        check(
+            a.min_childs >= 2.0 && a.min_childs <= a.max_pop,
+            format!(
+                "algogen.min_childs must be between 2 and max_pop ({}), got {}",
+                a.max_pop, a.min_childs
+            ),
+        );
+        check(
+            a.evaluation_cost >= 0.0,
+            format!(
+                "algogen.evaluation_cost must not be negative, got {}",
+                a.evaluation_cost
+            ),
+        );
+        check(
+            a.max_step > 0,
+            String::from("algogen.max_step must be positive"),
+        );
+        check(
+            !self.network.hidden.is_empty() && self.network.hidden.iter().all(|&h| h > 0),
+            format!(
+                "network.hidden must be a non empty list of positive sizes, got {:?}",
+                self.network.hidden
+            ),
+        );
+        check(
+            self.window.width > 0 && self.window.height > 0 && self.window.chart_width > 0,
+            format!(
+                "window size must be positive, got {}x{} and charts {} wide",
+                self.window.width, self.window.height, self.window.chart_width
+            ),
+        );
+        match errors.len() {
+            0 => Ok(()),
+            _ => Err(errors.join("\n")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the config is rejected and that the message names the setting.
+    fn rejected(config: Config, setting: &str) {
+        match config.validate() {
+            Ok(()) => panic!("{} was accepted", setting),
+            Err(e) => assert!(e.contains(setting), "{} not in : {}", setting, e),
+        }
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(Config::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn empty_file_gives_default_config() {
+        assert!(Config::parse("").is_ok());
+    }
+
+    #[test]
+    fn unknown_settings_are_rejected() {
+        assert!(Config::parse("[learning]\nnb_problem = 3").is_err());
+    }
+
+    #[test]
+    fn every_error_is_reported() {
+        let mut config = Config::default();
+        config.problem.size = 0;
+        config.learning.max_steps = 0;
+        let errors = config.validate().unwrap_err();
+        assert!(errors.contains("problem.size"));
+        assert!(errors.contains("learning.max_steps"));
+    }
+
+    #[test]
+    fn invalid_learning_settings_are_rejected() {
+        let cases: &[(&str, fn(&mut LearnParams))] = &[
+            ("learning.nb_problems", |l| l.nb_problems = 0),
+            ("learning.test_per_prob", |l| l.test_per_prob = 1),
+            ("learning.percent_elite", |l| l.percent_elite = 0.0),
+            ("learning.percent_elite", |l| l.percent_elite = 0.6),
+            ("learning.starting_coef", |l| l.starting_coef = -1.0),
+            ("learning.coef_mod", |l| l.coef_mod = 1.5),
+            ("learning.test_data_size", |l| l.test_data_size = 0),
+            ("learning.validation_data_size", |l| {
+                l.validation_data_size = 0
+            }),
+            ("learning.patience", |l| l.patience = 0),
+            ("learning.max_steps", |l| l.max_steps = 0),
+            ("learning.grad_step", |l| l.grad_step = 0.0),
+            ("learning.grad_batch_size", |l| l.grad_batch_size = 0),
+            ("learning.policy_step", |l| l.policy_step = 0.0),
+            ("learning.baseline_rate", |l| l.baseline_rate = 2.0),
+            ("learning.es_population", |l| l.es_population = 0),
+            ("learning.es_sigma", |l| l.es_sigma = 0.0),
+            ("learning.gamma", |l| l.gamma = 1.5),
+            ("learning.branch_factor", |l| l.branch_factor = 1),
+            ("learning.branch_horizon", |l| l.branch_horizon = 0),
+            ("learning.rank_baselines", |l| {
+                l.normalization = Normalization::Rank;
+                l.rank_baselines = 1;
+            }),
+            ("learning.curriculum", |l| l.curriculum = vec![0.5, 0.2]),
+            ("learning.curriculum", |l| l.curriculum = vec![0.0, 1.0]),
+            ("learning.curriculum_thresholds", |l| {
+                l.curriculum = vec![0.2, 0.5, 1.0];
+                l.curriculum_thresholds = vec![0.9];
+            }),
+            ("learning.history.memory", |l| l.history.memory = 2),
+            ("learning.replay.ratio", |l| l.replay.ratio = 1.0),
+            ("learning.replay.ratio", |l| l.replay.ratio = -0.1),
+            ("learning.replay.priority", |l| l.replay.priority = -1.0),
+            ("learning.exploration.scales", |l| {
+                l.exploration.scales = vec![-0.1]
+            }),
+            ("learning.exploration.ou_theta", |l| {
+                l.exploration.ou_theta = 0.0
+            }),
+            ("learning.exploration.coef_step", |l| {
+                l.exploration.coef_step = -1.0
+            }),
+        ];
+        for &(setting, change) in cases {
+            let mut config = Config::default();
+            change(&mut config.learning);
+            rejected(config, setting);
+        }
+    }
+
+    #[test]
+    fn memory_is_learned_by_evolution() {
+        let mut config = Config::default();
+        config.learning.scheme = TrainingScheme::Evolution;
+        config.learning.history.memory = 2;
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rank_baselines_only_matter_for_rank() {
+        let mut config = Config::default();
+        config.learning.normalization = Normalization::Ratio;
+        config.learning.rank_baselines = 0;
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_other_settings_are_rejected() {
+        let cases: &[(&str, fn(&mut Config))] = &[
+            ("problem.size", |c| c.problem.size = 0),
+            ("algogen.pop_start", |c| c.algogen.pop_start = 3.0),
+            ("algogen.death_start", |c| {
+                c.algogen.death_start = c.algogen.pop_start
+            }),
+            ("algogen starting parameters", |c| {
+                c.algogen.mut_start = -1.0
+            }),
+            ("algogen.min_childs", |c| c.algogen.min_childs = 1.0),
+            ("algogen.max_step", |c| c.algogen.max_step = 0),
+            ("algogen.evaluation_cost", |c| c.algogen.evaluation_cost = -1.0),
+            ("network.hidden", |c| c.network.hidden = vec![]),
+            ("network.hidden", |c| c.network.hidden = vec![10, 0]),
+            ("window size", |c| c.window.width = 0),
+        ];
+        for &(setting, change) in cases {
+            let mut config = Config::default();
+            change(&mut config);
+            rejected(config, setting);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! The exploration module
+//! The noise added to the outputs of the network while it plays to learn, and how its coef
+//! changes from one learning generation to the next.
+//!
+//! ```toml
+//! [learning.exploration]
+//! noise = "ornstein_uhlenbeck"
+//! scales = [0.5, 1, 1, 2, 1]
+//! schedule = "linear"
+//! ```
+use crate::params::*;
+use crate::random::GenRng;
+use crate::reilearn::TrainingScheme;
+use rand::distributions::{Distribution, Normal};
+use rand::Rng;
+use rulinalg::vector::Vector;
+use serde::{Deserialize, Serialize};
+
+/// Adds noise to the outputs of the network, the results stay in [0, 1].
+pub trait Exploration: Send {
+    /// The coef is the strength of the noise given by the schedule.
+    fn explore(&mut self, outputs: &Vector<f64>, coef: f64, rng: &mut GenRng) -> Vector<f64>;
+    /// Called before every playout, for the noises that depend on the previous steps.
+    fn reset(&mut self) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseKind {
+    /// Uniform in [-coef / 2, coef / 2].
+    Uniform,
+    /// Gaussian with the coef as deviation.
+    Gaussian,
+    /// With the coef as probability the output is replaced by a random one.
+    Epsilon,
+    /// Gaussian noise that is correlated with the one of the previous step and pulled back to 0.
+    OrnsteinUhlenbeck,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// The coef is multiplied by coef_mod every generation.
+    Exponential,
+    /// The coef loses coef_step every generation.
+    Linear,
+    /// The coef is multiplied by coef_mod when the playouts got better than ever and divided by it
+    /// otherwise, without going over the starting coef.
+    ScoreAdaptive,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExplorationSettings {
+    /// Gaussian for the Reinforce scheme and uniform for the others when not given.
+    pub noise: Option<NoiseKind>,
+    /// Multiplies the coef for every output, the missing ones use 1.
+    pub scales: Vec<f64>,
+    /// How fast the noise of the Ornstein-Uhlenbeck process goes back to 0, in ]0, 1].
+    pub ou_theta: f64,
+    pub schedule: Schedule,
+    pub coef_step: f64,
+    /// The schedules do not take the coef below it.
+    pub coef_min: f64,
+}
+
+impl Default for ExplorationSettings {
+    fn default() -> Self {
+        ExplorationSettings {
+            noise: None,
+            scales: vec![],
+            ou_theta: OU_THETA,
+            schedule: Schedule::Exponential,
+            coef_step: COEF_STEP,
+            coef_min: 0.0,
+        }
+    }
+}
+
+impl ExplorationSettings {
+    /// Creates the noise, the scheme chooses it when the settings do not.
+    pub fn build(&self, scheme: TrainingScheme) -> Box<dyn Exploration> {
+        let scales = Scales(self.scales.clone());
+        let noise = self.noise.unwrap_or(match scheme {
+            TrainingScheme::Reinforce => NoiseKind::Gaussian,
+            _ => NoiseKind::Uniform,
+        });
+        match noise {
+            NoiseKind::Uniform => Box::new(Uniform(scales)),
+            NoiseKind::Gaussian => Box::new(Gaussian(scales)),
+            NoiseKind::Epsilon => Box::new(Epsilon(scales)),
+            NoiseKind::OrnsteinUhlenbeck => Box::new(OrnsteinUhlenbeck {
+                scales,
+                theta: self.ou_theta,
+                state: vec![],
+            }),
+        }
+    }
+
+    /// Returns the coef of the next generation.
+    /// The score is the average score of the playouts of the generation that ended, the best is
+    /// the best of these averages before it.
+    pub fn next_coef(
+        &self,
+        coef: f64,
+        coef_mod: f64,
+        starting_coef: f64,
+        score: f64,
+        best: Option<f64>,
+    ) -> f64 {
+        let next = match self.schedule {
+            Schedule::Exponential => coef * coef_mod,
+            Schedule::Linear => coef - self.coef_step,
+            Schedule::ScoreAdaptive => match best {
+                Some(best) if score <= best => (coef / coef_mod).min(starting_coef),
+                _ => coef * coef_mod,
+            },
+        };
+        next.max(self.coef_min)
+    }
+}
+
+/// The coef of every output.
+#[derive(Debug, Clone)]
+struct Scales(Vec<f64>);
+
+impl Scales {
+    fn get(&self, index: usize, coef: f64) -> f64 {
+        coef * self.0.get(index).cloned().unwrap_or(1.0)
+    }
+}
+
+fn clamp(value: f64) -> f64 {
+    value.max(0.0).min(1.0)
+}
+
+struct Uniform(Scales);
+
+impl Exploration for Uniform {
+    fn explore(&mut self, outputs: &Vector<f64>, coef: f64, rng: &mut GenRng) -> Vector<f64> {
+        outputs
+            .iter()
+            .enumerate()
+            .map(|(i, val)| clamp(val + (rng.gen::<f64>() - 0.5) * self.0.get(i, coef)))
+            .collect()
+    }
+}
+
+struct Gaussian(Scales);
+
+impl Exploration for Gaussian {
+    fn explore(&mut self, outputs: &Vector<f64>, coef: f64, rng: &mut GenRng) -> Vector<f64> {
+        outputs
+            .iter()
+            .enumerate()
+            .map(|(i, val)| clamp(val + Normal::new(0.0, self.0.get(i, coef)).sample(rng)))
+            .collect()
+    }
+}
+
+struct Epsilon(Scales);
+
+impl Exploration for Epsilon {
+    fn explore(&mut self, outputs: &Vector<f64>, coef: f64, rng: &mut GenRng) -> Vector<f64> {
+        outputs
+            .iter()
+            .enumerate()
+            .map(|(i, val)| match rng.gen::<f64>() < self.0.get(i, coef) {
+                true => rng.gen::<f64>(),
+                false => *val,
+            })
+            .collect()
+    }
+}
+
+struct OrnsteinUhlenbeck {
+    scales: Scales,
+    theta: f64,
+    /// The noise of the previous step, for every output.
+    state: Vec<f64>,
+}
+
+impl Exploration for OrnsteinUhlenbeck {
+    fn explore(&mut self, outputs: &Vector<f64>, coef: f64, rng: &mut GenRng) -> Vector<f64> {
+        self.state.resize(outputs.size(), 0.0);
+        let normal = Normal::new(0.0, 1.0);
+        for (i, noise) in self.state.iter_mut().enumerate() {
+            *noise += -self.theta * *noise + self.scales.get(i, coef) * normal.sample(rng);
+        }
+        outputs
+            .iter()
+            .zip(self.state.iter())
+            .map(|(val, noise)| clamp(val + noise))
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        self.state.clear();
+    }
+}
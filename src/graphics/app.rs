@@ -1,41 +1,127 @@
 use cairo::Context;
+use crate::config::{Config, WindowSettings};
 use crate::graphics::SingleStepDrawable;
-use crate::graphics::{learn_back, DrawInstruction, Entity, ToDraw};
-use crate::params::*;
+use crate::graphics::charts::{decision_shapes, TrainingCurves, CHARTS_WORLD, DECISIONS_WORLD};
+use crate::graphics::control::{Command, Progress, Trainer};
+use crate::graphics::raster::frames_of;
+use crate::graphics::settings::SettingsView;
+use crate::graphics::shape::ordered;
+use crate::graphics::{DrawInstruction, Layered, Scene, Shape, ToDraw};
 use crate::problems;
+use crate::random::{self, GenRng};
+use crate::trace::Trace;
 use gtk::prelude::*;
-use gtk::{Button, DrawingArea, ScrolledWindow, Window, WindowType};
+use gtk::{
+    Button, CheckButton, DrawingArea, Entry, Scale, ScrolledWindow, SpinButton, ToggleButton,
+    Window, WindowType,
+};
 use std::marker::PhantomData;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use std::thread;
 
+/// Time between two checks of the playback, in ms.
+const TICK: u32 = 20;
+/// Time a frame is shown at normal speed, in ms.
+const FRAME_TIME: f64 = 200.0;
+/// The charts and the decisions are redrawn every this number of ticks.
+const CHARTS_TICKS: u32 = 10;
+
+/// Creates the config of the problems from the settings chosen in the window.
+pub type ConfParser<C> = Rc<dyn Fn(&Config) -> Result<C, String>>;
+
+#[derive(Clone)]
 struct Shared {
-    current_frames: Arc<Mutex<ToDraw>>,
     next_frames: Arc<Mutex<ToDraw>>,
-    state: Arc<Mutex<State>>,
-    world_size: Arc<Mutex<[usize; 2]>>,
+    playback: Arc<Mutex<Playback>>,
+    curves: Arc<Mutex<TrainingCurves>>,
+    /// The learning thread running.
+    trainer: Arc<Mutex<Option<Trainer>>>,
+    /// Every learning thread draws its generator from this one.
+    rng: Arc<Mutex<GenRng>>,
 }
 
 impl Shared {
     pub fn new() -> Self {
         Shared {
-            current_frames: Arc::new(Mutex::new(ToDraw(Vec::new()))),
-            next_frames: Arc::new(Mutex::new(ToDraw(Vec::new()))),
-            state: Arc::new(Mutex::new(State {
+            next_frames: Arc::new(Mutex::new(ToDraw::new())),
+            playback: Arc::new(Mutex::new(Playback {
+                frames: vec![],
+                decisions: Trace::new(),
+                position: 0,
+                compare: false,
                 run: true,
-                repaint: false,
-                exited: false,
+                speed: 1.0,
+                elapsed: 0.0,
             })),
-            world_size: Arc::new(Mutex::new([1, 1])),
+            curves: Arc::new(Mutex::new(TrainingCurves::default())),
+            trainer: Arc::new(Mutex::new(None)),
+            rng: Arc::new(Mutex::new(random::from_entropy())),
+        }
+    }
+
+    /// Sends the command to the learning thread if there is one.
+    fn send(&self, command: Command) {
+        if let Some(ref trainer) = *self.trainer.lock().unwrap() {
+            trainer.send(command);
         }
     }
 }
 
-// `State` is used for handling events
-struct State {
+/// The demo being shown and where we are in it.
+struct Playback {
+    frames: Vec<([usize; 2], Scene)>,
+    /// What the supervisor chose during the demo.
+    decisions: Trace,
+    position: usize,
+    /// Shows the best solutions of the network and of the baseline instead of the demo.
+    compare: bool,
     run: bool,
-    repaint: bool,
-    exited: bool,
+    /// 1.0 shows a frame every FRAME_TIME.
+    speed: f64,
+    /// Time since the frame is shown, in ms.
+    elapsed: f64,
+}
+
+impl Playback {
+    fn current(&self) -> Option<&([usize; 2], Scene)> {
+        self.frames.get(self.position)
+    }
+
+    /// Goes to the next frame, or to the start of the latest demo at the end of this one.
+    fn step(&mut self, next: &ToDraw) {
+        self.elapsed = 0.0;
+        if self.position + 1 < self.frames.len() {
+            self.position += 1;
+        } else {
+            self.load(next);
+        }
+    }
+
+    /// Starts the latest demo, or its comparison to the baseline.
+    fn load(&mut self, next: &ToDraw) {
+        self.elapsed = 0.0;
+        self.frames = frames_of(match self.compare {
+            true => &next.comparison,
+            false => &next.frames,
+        });
+        self.decisions = next.decisions.clone();
+        self.position = 0;
+    }
+
+    /// How far in the demo we are, between 0 and 1.
+    fn progress(&self) -> f64 {
+        self.position as f64 / self.frames.len().max(1) as f64
+    }
+
+    /// Lets the time pass, returns the position and the number of frames if the frame changed.
+    fn advance(&mut self, time: f64, next: &ToDraw) -> Option<(usize, usize)> {
+        self.elapsed += time;
+        if !self.run || (self.elapsed < FRAME_TIME / self.speed && !self.frames.is_empty()) {
+            return None;
+        }
+        self.step(next);
+        Some((self.position, self.frames.len()))
+    }
 }
 
 // DrawingArea is wrapped in Area, look here:
@@ -48,6 +134,8 @@ unsafe impl Send for Area {}
 pub struct App<T: SingleStepDrawable + Clone> {
     shared: Shared,
     view: View,
+    /// Without it the problem can not be changed, only the learning settings.
+    parse_conf: Option<ConfParser<T::ProblemConfig>>,
     phantom: PhantomData<T>,
 }
 
@@ -62,139 +150,405 @@ where
         Ok(App {
             shared,
             view,
+            parse_conf: None,
             phantom: PhantomData,
         })
     }
 
-    pub fn start(&mut self, conf: T::ProblemConfig) {
-        self.view.pack_all();
+    /// Makes the learning reproducible : the networks and the problems of every learning started
+    /// from the window come from this generator.
+    pub fn with_rng(self, rng: GenRng) -> Self {
+        *self.shared.rng.lock().unwrap() = rng;
+        self
+    }
+
+    /// Lets the problem be chosen in the window, among the given ones or any other that parse
+    /// accepts.
+    pub fn with_problems<F>(mut self, problems: &[String], parse: F) -> Self
+    where
+        F: Fn(&Config) -> Result<T::ProblemConfig, String> + 'static,
+    {
+        self.view.settings.add_problems(problems);
+        self.parse_conf = Some(Rc::new(parse));
+        self
+    }
+
+    pub fn start(&mut self, conf: T::ProblemConfig, config: Config) {
+        self.view.pack_all(&config.window);
+        self.view.settings.fill(&config);
         self.connect_close();
-        self.connect_draw();
-        self.run(conf);
+        self.connect_restart(conf.clone(), config.clone());
+        self.connect_draw(&config.window);
+        self.connect_draw_charts(&config.window);
+        self.connect_draw_decisions(&config.window);
+        self.connect_controls();
+        self.connect_learning_controls(&config);
+        self.run(conf, config);
     }
 
-    /// Draws the given entities on the area using the given world size.
+    /// Draws the given shapes on the area using the given world size.
     fn draw_entities(
         _this: &DrawingArea,
         cr: &Context,
-        frame: Vec<Entity>,
+        frame: Vec<Shape>,
         world_size: &[usize; 2],
+        window: &WindowSettings,
     ) -> Inhibit {
-        //println!("draw instruction entities! {}", frame.len());
         cr.set_source_rgb(1f64, 1f64, 1f64);
         cr.paint();
         let (coef_x, coef_y) = (
-            (WIDTH as f64) / (world_size[0] as f64),
-            (HEIGHT as f64) / (world_size[1] as f64),
+            (window.width as f64) / (world_size[0] as f64),
+            (window.height as f64) / (world_size[1] as f64),
         );
-        for [x, y, size_x, size_y, red, green, blue] in frame {
-            cr.set_source_rgb(red, green, blue);
-            cr.rectangle(coef_x * x, coef_y * y, coef_x * size_x, coef_y * size_y);
-            /*println!(
-                "draw ent : {}, {}, {}, {}, {}, {}, {}",
-                coef_x * x,
-                coef_y * y,
-                coef_x * size_x,
-                coef_y * size_y,
-                red,
-                green,
-                blue
-            );
-            println!("draw orig : {}, {}, {}, {}", x, y, size_x, size_y);*/
-            cr.fill();
+        for shape in frame {
+            Self::draw_shape(cr, &shape.scaled(coef_x, coef_y));
         }
         Inhibit(true)
     }
 
-    fn draw_instruction(
-        this: &DrawingArea,
-        cr: &Context,
-        current: &mut ToDraw,
-        next: &mut ToDraw,
-        world_size: &mut [usize; 2],
-    ) -> Inhibit {
-        let front = current.0.pop();
-        match front {
-            Some(DrawInstruction::WorldSize([x, y])) => {
-                *world_size = [x, y];
-                Self::draw_instruction(this, cr, current, next, world_size)
+    /// Draws a shape given in pixels.
+    fn draw_shape(cr: &Context, shape: &Shape) {
+        match shape {
+            Shape::Rect { pos, size, color } => {
+                cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+                cr.rectangle(pos[0], pos[1], size[0], size[1]);
+                cr.fill();
+            }
+            Shape::Circle {
+                center,
+                radius,
+                color,
+            } => {
+                cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+                cr.arc(center[0], center[1], *radius, 0.0, 2.0 * std::f64::consts::PI);
+                cr.fill();
             }
-            Some(DrawInstruction::Frame(frame)) => Self::draw_entities(this, cr, frame, world_size),
-            None => {
-                current.0 = next.0.clone().into_iter().rev().collect();
-                if current.0.len() > 0 {
-                    Self::draw_instruction(this, cr, current, next, world_size)
-                } else {
-                    Inhibit(true)
+            Shape::Line {
+                points,
+                width,
+                color,
+            } => {
+                cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+                cr.set_line_width(width.max(1.0));
+                for (i, p) in points.iter().enumerate() {
+                    match i {
+                        0 => cr.move_to(p[0], p[1]),
+                        _ => cr.line_to(p[0], p[1]),
+                    }
                 }
+                cr.stroke();
+            }
+            Shape::Polygon { points, color } => {
+                cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+                for (i, p) in points.iter().enumerate() {
+                    match i {
+                        0 => cr.move_to(p[0], p[1]),
+                        _ => cr.line_to(p[0], p[1]),
+                    }
+                }
+                cr.close_path();
+                cr.fill();
+            }
+            Shape::Text {
+                pos,
+                height,
+                text,
+                color,
+            } => {
+                cr.set_source_rgba(color[0], color[1], color[2], color[3]);
+                cr.set_font_size(height * 1.4);
+                cr.move_to(pos[0], pos[1] + height);
+                cr.show_text(text);
             }
         }
     }
 
-    fn connect_draw(&mut self) {
-        // also update the map each time we re-draw
-        let current_frames = self.shared.current_frames.clone();
-        let next_frames = self.shared.next_frames.clone();
-        let world_size = self.shared.world_size.clone();
+    fn connect_draw(&mut self, window: &WindowSettings) {
+        let playback = self.shared.playback.clone();
+        let window = window.clone();
         self.view.area.0.connect_draw(move |this, cr| {
-            let mut current = current_frames.lock().unwrap();
-            let mut next = next_frames.lock().unwrap();
-            let mut world = world_size.lock().unwrap();
-            this.set_size_request(WIDTH, HEIGHT);
-            Self::draw_instruction(this, cr, &mut current, &mut next, &mut world)
+            this.set_size_request(window.width, window.height);
+            match playback.lock().unwrap().current() {
+                Some((world_size, scene)) => {
+                    Self::draw_entities(this, cr, scene.clone(), world_size, &window)
+                }
+                None => Inhibit(true),
+            }
         });
     }
 
-    fn spawn_main_thread(&mut self) -> thread::JoinHandle<()> {
-        let state = self.shared.state.clone();
-        let area = self.view.area.clone();
+    /// Paints the shapes of a world on an area of the given size.
+    fn draw_world(cr: &Context, shapes: Vec<Layered>, world_size: [usize; 2], size: [i32; 2]) {
+        cr.set_source_rgb(1f64, 1f64, 1f64);
+        cr.paint();
+        let (coef_x, coef_y) = (
+            (size[0] as f64) / (world_size[0] as f64),
+            (size[1] as f64) / (world_size[1] as f64),
+        );
+        if let Some(scene) = ordered(&DrawInstruction::Shapes(shapes)) {
+            for shape in scene {
+                Self::draw_shape(cr, &shape.scaled(coef_x, coef_y));
+            }
+        }
+    }
 
-        thread::spawn(move || {
-            let duration = std::time::Duration::from_millis(200);
-            loop {
-                thread::sleep(duration);
-                let mut state = state.lock().unwrap();
-                if state.exited {
-                    break;
-                }
-                if state.run {
-                    // `queue_draw` will ask gtk to
-                    // repaint the widget
-                    area.0.queue_draw();
-                } else if state.repaint {
-                    state.repaint = false;
+    /// Draws the learning curves, they are filled with the events of the learning thread.
+    fn connect_draw_charts(&mut self, window: &WindowSettings) {
+        let curves = self.shared.curves.clone();
+        let size = [window.chart_width, window.height];
+        self.view.charts.0.connect_draw(move |_, cr| {
+            let shapes = curves.lock().unwrap().shapes();
+            Self::draw_world(cr, shapes, CHARTS_WORLD, size);
+            Inhibit(true)
+        });
+    }
+
+    /// Draws the decisions of the supervisor during the demo shown.
+    fn connect_draw_decisions(&mut self, window: &WindowSettings) {
+        let playback = self.shared.playback.clone();
+        let size = [window.chart_width * 2, window.height];
+        self.view.decisions.0.connect_draw(move |_, cr| {
+            let shapes = {
+                let playback = playback.lock().unwrap();
+                decision_shapes(&playback.decisions, playback.progress())
+            };
+            Self::draw_world(cr, shapes, DECISIONS_WORLD, size);
+            Inhibit(true)
+        });
+    }
+
+    /// Moves the scrubber to the frame shown.
+    fn show_position(scrubber: &Scale, position: usize, len: usize) {
+        scrubber.set_range(0.0, (len.max(2) - 1) as f64);
+        scrubber.set_value(position as f64);
+    }
+
+    /// Connects the play/pause, step, replay and compare buttons, the speed and the scrubber.
+    /// The playback is never locked while a widget is changed because it fires their handlers.
+    fn connect_controls(&mut self) {
+        {
+            let playback = self.shared.playback.clone();
+            self.view.pause_button.connect_clicked(move |button| {
+                let run = {
+                    let mut playback = playback.lock().unwrap();
+                    playback.run = !playback.run;
+                    playback.run
+                };
+                button.set_label(if run { "Pause" } else { "Play" });
+            });
+        }
+        {
+            let playback = self.shared.playback.clone();
+            let next = self.shared.next_frames.clone();
+            let area = self.view.area.clone();
+            let scrubber = self.view.scrubber.clone();
+            let pause_button = self.view.pause_button.clone();
+            self.view.step_button.connect_clicked(move |_| {
+                let (position, len) = {
+                    let mut playback = playback.lock().unwrap();
+                    playback.run = false;
+                    playback.step(&next.lock().unwrap());
+                    (playback.position, playback.frames.len())
+                };
+                pause_button.set_label("Play");
+                Self::show_position(&scrubber, position, len);
+                area.0.queue_draw();
+            });
+        }
+        {
+            let playback = self.shared.playback.clone();
+            let area = self.view.area.clone();
+            let scrubber = self.view.scrubber.clone();
+            let pause_button = self.view.pause_button.clone();
+            self.view.replay_button.connect_clicked(move |_| {
+                let len = {
+                    let mut playback = playback.lock().unwrap();
+                    playback.run = true;
+                    playback.position = 0;
+                    playback.elapsed = 0.0;
+                    playback.frames.len()
+                };
+                pause_button.set_label("Pause");
+                Self::show_position(&scrubber, 0, len);
+                area.0.queue_draw();
+            });
+        }
+        {
+            let playback = self.shared.playback.clone();
+            let next = self.shared.next_frames.clone();
+            let area = self.view.area.clone();
+            let scrubber = self.view.scrubber.clone();
+            self.view.compare_button.connect_toggled(move |button| {
+                let len = {
+                    let mut playback = playback.lock().unwrap();
+                    playback.compare = button.get_active();
+                    playback.load(&next.lock().unwrap());
+                    playback.frames.len()
+                };
+                Self::show_position(&scrubber, 0, len);
+                area.0.queue_draw();
+            });
+        }
+        {
+            let playback = self.shared.playback.clone();
+            self.view.speed.connect_value_changed(move |speed| {
+                playback.lock().unwrap().speed = speed.get_value();
+            });
+        }
+        {
+            let playback = self.shared.playback.clone();
+            let area = self.view.area.clone();
+            self.view.scrubber.connect_value_changed(move |scrubber| {
+                let position = scrubber.get_value().round() as usize;
+                let mut playback = playback.lock().unwrap();
+                // the value also changes when the frames are played
+                if position != playback.position && position < playback.frames.len() {
+                    playback.position = position;
+                    playback.elapsed = 0.0;
                     area.0.queue_draw();
                 }
-            }
-        })
+            });
+        }
+    }
+
+    /// Connects the buttons sending commands to the learning thread, it answers with a status.
+    fn connect_learning_controls(&mut self, config: &Config) {
+        {
+            let shared = self.shared.clone();
+            self.view.learning_pause.connect_toggled(move |button| {
+                shared.send(match button.get_active() {
+                    true => Command::Pause,
+                    false => Command::Resume,
+                });
+            });
+        }
+        {
+            let shared = self.shared.clone();
+            self.view.new_demo_button.connect_clicked(move |_| {
+                shared.send(Command::NewDemo);
+            });
+        }
+        {
+            let shared = self.shared.clone();
+            let coef = self.view.coef.clone();
+            self.view.coef.set_value(config.learning.starting_coef);
+            self.view.coef_button.connect_clicked(move |_| {
+                shared.send(Command::SetCoef(coef.get_value()));
+            });
+        }
+        {
+            let shared = self.shared.clone();
+            let save_path = self.view.save_path.clone();
+            self.view.save_button.connect_clicked(move |_| {
+                shared.send(Command::Save(save_path.get_text().unwrap_or_default()));
+            });
+        }
     }
 
-    pub fn spawn_learning_thread(&mut self, conf: T::ProblemConfig) -> thread::JoinHandle<()> {
+    /// Plays the frames, reads what the learning thread sent and refreshes the charts, in the gtk
+    /// main loop.
+    fn start_playback(&mut self) {
+        let shared = self.shared.clone();
+        let playback = self.shared.playback.clone();
         let next = self.shared.next_frames.clone();
-        let arc_conf = Arc::new(Mutex::new(conf));
-        thread::spawn(move || learn_back::<T>(next, arc_conf))
+        let area = self.view.area.clone();
+        let charts = self.view.charts.clone();
+        let decisions = self.view.decisions.clone();
+        let scrubber = self.view.scrubber.clone();
+        let settings = self.view.settings.clone();
+        let mut ticks = 0;
+        gtk::timeout_add(TICK, move || {
+            ticks += 1;
+            Self::receive(&shared, &settings);
+            if ticks % CHARTS_TICKS == 0 {
+                charts.0.queue_draw();
+                decisions.0.queue_draw();
+            }
+            let moved = playback
+                .lock()
+                .unwrap()
+                .advance(TICK as f64, &next.lock().unwrap());
+            if let Some((position, len)) = moved {
+                Self::show_position(&scrubber, position, len);
+                area.0.queue_draw();
+            }
+            gtk::Continue(true)
+        });
     }
 
-    pub fn connect_close(&mut self) {
+    /// Takes what the learning thread sent : the events go to the curves and the demos wait for the
+    /// end of the one shown.
+    fn receive(shared: &Shared, settings: &SettingsView) {
+        if let Some(ref trainer) = *shared.trainer.lock().unwrap() {
+            for message in trainer.received() {
+                match message {
+                    Progress::Event(event) => shared.curves.lock().unwrap().update(&event),
+                    Progress::Demo(demo) => *shared.next_frames.lock().unwrap() = demo,
+                    Progress::Status(status) => settings.set_status(&status),
+                    Progress::Finished => settings.set_status("learning stopped"),
+                }
+            }
+        }
+    }
+
+    /// Starts learning in a new thread, stopping the previous one.
+    /// The previous thread is not waited for, what it still sends is never read.
+    fn start_learning(shared: &Shared, conf: T::ProblemConfig, config: Config) {
+        if let Some(previous) = shared.trainer.lock().unwrap().take() {
+            previous.send(Command::Shutdown);
+        }
+        *shared.next_frames.lock().unwrap() = ToDraw::new();
+        *shared.curves.lock().unwrap() = TrainingCurves::default();
         {
-            let state = self.shared.state.clone();
-            self.view.window.connect_delete_event(move |_, _| {
-                let mut state = state.lock().unwrap();
-                state.exited = true;
-                gtk::main_quit();
-                Inhibit(true)
-            });
+            let mut playback = shared.playback.lock().unwrap();
+            playback.frames.clear();
+            playback.decisions = Trace::new();
+            playback.position = 0;
         }
+        let rng = random::derive(&mut shared.rng.lock().unwrap());
+        *shared.trainer.lock().unwrap() = Some(Trainer::spawn::<T>(conf, config, rng));
     }
 
-    fn run(&mut self, conf: T::ProblemConfig) {
-        let main_thread = self.spawn_main_thread();
-        let _learning_thread = self.spawn_learning_thread(conf);
+    /// Restarts the learning with the settings of the widgets.
+    fn connect_restart(&mut self, conf: T::ProblemConfig, base: Config) {
+        let shared = self.shared.clone();
+        let settings = self.view.settings.clone();
+        let learning_pause = self.view.learning_pause.clone();
+        let parse_conf = self.parse_conf.clone();
+        self.view
+            .settings
+            .restart_button
+            .connect_clicked(move |_| {
+                let restarted = settings.read(&base).and_then(|config| {
+                    let conf = match parse_conf {
+                        Some(ref parse) => parse(&config)?,
+                        None => conf.clone(),
+                    };
+                    let status = format!("learning on {}", config.problem.name);
+                    learning_pause.set_active(false);
+                    Self::start_learning(&shared, conf, config);
+                    Ok(status)
+                });
+                match restarted {
+                    Ok(status) => settings.set_status(&status),
+                    Err(e) => settings.set_status(&e),
+                }
+            });
+    }
+
+    pub fn connect_close(&mut self) {
+        self.view.window.connect_delete_event(move |_, _| {
+            gtk::main_quit();
+            Inhibit(true)
+        });
+    }
+
+    fn run(&mut self, conf: T::ProblemConfig, config: Config) {
+        self.start_playback();
+        Self::start_learning(&self.shared, conf, config);
         gtk::main();
-        // wait for the thread to stop
-        match main_thread.join() {
-            Err(_) => println!("Some error ocured"),
-            Ok(_) => (),
+        if let Some(trainer) = self.shared.trainer.lock().unwrap().take() {
+            trainer.shutdown();
         }
     }
 }
@@ -202,9 +556,24 @@ where
 struct View {
     window: Window,
     hbox: gtk::Box,
+    vbox: gtk::Box,
     button_box: gtk::ButtonBox,
     pause_button: gtk::Button,
+    step_button: gtk::Button,
+    replay_button: gtk::Button,
+    compare_button: gtk::CheckButton,
+    speed: Scale,
+    learning_pause: ToggleButton,
+    new_demo_button: Button,
+    coef: SpinButton,
+    coef_button: Button,
+    save_path: Entry,
+    save_button: Button,
+    scrubber: Scale,
     area: Area,
+    charts: Area,
+    decisions: Area,
+    settings: SettingsView,
     scroller: ScrolledWindow,
 }
 
@@ -213,32 +582,94 @@ impl View {
         gtk::init().map_err(|_| String::from("gtk::init failed"))?;
         let window = Window::new(WindowType::Toplevel);
         let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        let vbox = gtk::Box::new(gtk::Orientation::Vertical, 0);
         let button_box = gtk::ButtonBox::new(gtk::Orientation::Vertical);
-        let pause_button = Button::new_with_label("Start");
+        let pause_button = Button::new_with_label("Pause");
+        let step_button = Button::new_with_label("Step");
+        let replay_button = Button::new_with_label("Replay");
+        let compare_button = CheckButton::new_with_label("Next to baseline");
+        let speed = Scale::new_with_range(gtk::Orientation::Horizontal, 0.25, 4.0, 0.25);
+        let scrubber = Scale::new_with_range(gtk::Orientation::Horizontal, 0.0, 1.0, 1.0);
+        let learning_pause = ToggleButton::new_with_label("Pause learning");
+        let new_demo_button = Button::new_with_label("Demo on a new problem");
+        let coef = SpinButton::new_with_range(0.0, 10.0, 0.05);
+        let coef_button = Button::new_with_label("Set exploration");
+        let save_path = Entry::new();
+        let save_button = Button::new_with_label("Save network");
         let area = Area(DrawingArea::new());
+        let charts = Area(DrawingArea::new());
+        let decisions = Area(DrawingArea::new());
+        let settings = SettingsView::new();
         let scroller = ScrolledWindow::new(None, None);
         Ok(View {
             window,
             hbox,
+            vbox,
             button_box,
             pause_button,
+            step_button,
+            replay_button,
+            compare_button,
+            speed,
+            learning_pause,
+            new_demo_button,
+            coef,
+            coef_button,
+            save_path,
+            save_button,
+            scrubber,
             area,
+            charts,
+            decisions,
+            settings,
             scroller,
         })
     }
 
-    pub fn pack_all(&mut self) {
+    pub fn pack_all(&mut self, window: &WindowSettings) {
         println!("pack all");
-        self.scroller.set_size_request(WIDTH, HEIGHT);
+        self.scroller.set_size_request(window.width, window.height);
         // disable auto-hide scrollbar
         self.scroller.set_overlay_scrolling(false);
 
         self.button_box.set_layout(gtk::ButtonBoxStyle::Start);
         self.button_box
             .pack_start(&self.pause_button, false, false, 0);
+        self.button_box
+            .pack_start(&self.step_button, false, false, 0);
+        self.button_box
+            .pack_start(&self.replay_button, false, false, 0);
+        self.button_box
+            .pack_start(&self.compare_button, false, false, 0);
+        self.speed.set_value(1.0);
+        self.button_box.pack_start(&self.speed, false, false, 0);
+        self.coef.set_digits(2);
+        self.save_path.set_text("network.txt");
+        let learning: [&gtk::Widget; 6] = [
+            self.learning_pause.upcast_ref(),
+            self.new_demo_button.upcast_ref(),
+            self.coef.upcast_ref(),
+            self.coef_button.upcast_ref(),
+            self.save_path.upcast_ref(),
+            self.save_button.upcast_ref(),
+        ];
+        for widget in learning.iter() {
+            self.button_box.pack_start(*widget, false, false, 0);
+        }
+        self.scrubber.set_digits(0);
         self.scroller.add(&self.area.0);
-        self.hbox.pack_start(&self.scroller, false, false, 0);
+        self.vbox.pack_start(&self.scroller, false, false, 0);
+        self.vbox.pack_start(&self.scrubber, false, false, 0);
+        self.hbox.pack_start(&self.vbox, false, false, 0);
+        self.charts.0.set_size_request(window.chart_width, window.height);
+        self.hbox.pack_start(&self.charts.0, false, false, 0);
+        self.decisions
+            .0
+            .set_size_request(window.chart_width * 2, window.height);
+        self.hbox.pack_start(&self.decisions.0, false, false, 0);
         self.hbox.pack_start(&self.button_box, false, false, 0);
+        self.hbox
+            .pack_start(&self.settings.container, false, false, 0);
         self.window.add(&self.hbox);
         self.window.set_title("Neugene");
         self.window.show_all();
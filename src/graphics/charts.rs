@@ -0,0 +1,224 @@
+//! Charts of the learning progress and of the supervisor's decisions, drawn next to the problems
+//! in the window.
+//! The curves are filled with the events sent by the learning thread, and drawn with the usual
+//! shapes.
+use crate::algogen::ParamChoice;
+use crate::graphics::{rgb, Color, Layered, Point, Shape};
+use crate::observer::Event;
+use crate::trace::Trace;
+
+/// The world the charts are drawn in, three charts on top of each other.
+pub const CHARTS_WORLD: [usize; 2] = [100, 150];
+
+/// The world the decisions are drawn in, a row per parameter with the outputs of the network on
+/// the left and the parameters on the right.
+pub const DECISIONS_WORLD: [usize; 2] = [200, 200];
+
+/// Size of a chart without its title.
+const CHART_SIZE: Point = [90.0, 30.0];
+/// Space taken by the title above the chart.
+const TITLE_SPACE: f64 = 6.0;
+
+/// Everything reported by the learner, one value per learning generation.
+#[derive(Debug, Clone, Default)]
+pub struct TrainingCurves {
+    pub scores: Vec<f64>,
+    pub baseline: Option<f64>,
+    pub coefs: Vec<f64>,
+    pub examples: Vec<usize>,
+}
+
+impl TrainingCurves {
+    pub fn update(&mut self, event: &Event) {
+        match event {
+            Event::ValidationScoreComputed { score } => self.scores.push(*score),
+            Event::BaselineComputed { score } => self.baseline = Some(*score),
+            Event::NetworkRetrained { examples, coef } => {
+                self.examples.push(*examples);
+                self.coefs.push(*coef);
+            }
+            _ => (),
+        }
+    }
+
+    /// Draws the validation score against the baseline, the exploration coef and the number of
+    /// examples, in the charts world.
+    pub fn shapes(&self) -> Vec<Layered> {
+        let mut shapes = vec![];
+        let baseline = self
+            .baseline
+            .map(|b| vec![b; self.scores.len().max(2)])
+            .unwrap_or_default();
+        let size = [CHART_SIZE[0], 40.0];
+        chart(
+            &mut shapes,
+            [5.0, 0.0],
+            size,
+            "validation score",
+            &[
+                (&baseline, rgb(0.6, 0.6, 0.6)),
+                (&self.scores, rgb(0.1, 0.3, 0.9)),
+            ],
+        );
+        chart(
+            &mut shapes,
+            [5.0, 50.0],
+            size,
+            "exploration",
+            &[(&self.coefs, rgb(0.9, 0.5, 0.1))],
+        );
+        let examples = self.examples.iter().map(|&e| e as f64).collect::<Vec<f64>>();
+        chart(
+            &mut shapes,
+            [5.0, 100.0],
+            size,
+            "examples",
+            &[(&examples, rgb(0.1, 0.7, 0.2))],
+        );
+        shapes
+    }
+}
+
+/// Draws the values the supervisor chose during a run, the progress between 0 and 1 is marked
+/// on every chart.
+pub fn decision_shapes(trace: &Trace, progress: f64) -> Vec<Layered> {
+    let mut shapes = vec![];
+    let names = ["global", "mutrate", "elite", "kills", "birth_rate"];
+    let get = |p: &ParamChoice, index: usize| match index {
+        0 => p.global,
+        1 => p.mutrate,
+        2 => p.elite,
+        3 => p.kills,
+        _ => p.birth_rate,
+    };
+    let rows = trace.rows();
+    for (index, name) in names.iter().enumerate() {
+        let top = index as f64 * 40.0;
+        let outputs = rows.iter().map(|r| get(&r.choice, index)).collect::<Vec<f64>>();
+        let requested = rows.iter().map(|r| get(&r.requested, index)).collect::<Vec<f64>>();
+        let applied = rows.iter().map(|r| get(&r.params, index)).collect::<Vec<f64>>();
+        chart(
+            &mut shapes,
+            [5.0, top],
+            CHART_SIZE,
+            &format!("{} output", name),
+            &[(&outputs, rgb(0.5, 0.2, 0.8))],
+        );
+        chart(
+            &mut shapes,
+            [105.0, top],
+            CHART_SIZE,
+            &format!("{} applied", name),
+            &[
+                (&requested, rgb(0.6, 0.6, 0.6)),
+                (&applied, rgb(0.1, 0.3, 0.9)),
+            ],
+        );
+        for &left in [5.0, 105.0].iter() {
+            let x = left + CHART_SIZE[0] * progress.max(0.0).min(1.0);
+            shapes.push(
+                Shape::Line {
+                    points: vec![
+                        [x, top + TITLE_SPACE],
+                        [x, top + TITLE_SPACE + CHART_SIZE[1]],
+                    ],
+                    width: 0.4,
+                    color: rgb(0.9, 0.1, 0.1),
+                }
+                .on(3),
+            );
+        }
+    }
+    shapes
+}
+
+/// Draws the series in a chart with its title above, the last value of the last series is in the
+/// title.
+fn chart(
+    shapes: &mut Vec<Layered>,
+    origin: Point,
+    size: Point,
+    title: &str,
+    series: &[(&Vec<f64>, Color)],
+) {
+    let (mut min, mut max) = series
+        .iter()
+        .flat_map(|(values, _)| values.iter())
+        .filter(|v| v.is_finite())
+        .fold((std::f64::INFINITY, std::f64::NEG_INFINITY), |(min, max), &v| {
+            (min.min(v), max.max(v))
+        });
+    if min > max {
+        min = 0.0;
+        max = 1.0;
+    } else if min == max {
+        min -= 0.5;
+        max += 0.5;
+    }
+    let last = series.last().and_then(|(values, _)| values.last());
+    let (left, box_top) = (origin[0], origin[1] + TITLE_SPACE);
+    shapes.push(
+        Shape::Text {
+            pos: [left, origin[1] + 1.5],
+            height: 3.0,
+            text: match last {
+                Some(value) => format!("{} {:.4}", title, value),
+                None => String::from(title),
+            },
+            color: rgb(0.0, 0.0, 0.0),
+        }
+        .on(0),
+    );
+    shapes.push(
+        Shape::Rect {
+            pos: [left, box_top],
+            size,
+            color: rgb(0.95, 0.95, 0.95),
+        }
+        .on(0),
+    );
+    for (value, y) in [(max, box_top + 1.0), (min, box_top + size[1] - 3.0)].iter() {
+        shapes.push(
+            Shape::Text {
+                pos: [left + 1.0, *y],
+                height: 2.0,
+                text: format!("{:.3}", value),
+                color: rgb(0.4, 0.4, 0.4),
+            }
+            .on(2),
+        );
+    }
+    for (values, color) in series.iter() {
+        let step = size[0] / (values.len().max(2) - 1) as f64;
+        let points = values
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.is_finite())
+            .map(|(i, v)| {
+                [
+                    left + step * i as f64,
+                    box_top + size[1] * (1.0 - (v - min) / (max - min)),
+                ]
+            })
+            .collect::<Vec<[f64; 2]>>();
+        if points.len() == 1 {
+            shapes.push(
+                Shape::Circle {
+                    center: points[0],
+                    radius: 0.8,
+                    color: *color,
+                }
+                .on(1),
+            );
+        } else {
+            shapes.push(
+                Shape::Line {
+                    points,
+                    width: 0.6,
+                    color: *color,
+                }
+                .on(1),
+            );
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Shows the best solutions of several runs of the same problem next to each other, to see what
+//! the network changes compared to the fixed parameters.
+use crate::algogen::AlgoGen;
+use crate::graphics::raster::frames_of;
+use crate::graphics::{rgb, DrawInstruction, Layered, Shape, SingleStepDrawable};
+use crate::problems::{SingleStepProblem, Solution};
+
+/// Height of a line of the labels above the runs, as a part of the world's height.
+const LABEL_LINE: f64 = 0.05;
+
+/// Draws the best solution of every run in its own column, with the name of the run, the number
+/// of evaluations and the best score above it.
+/// The shorter animations stay on their last frame until the longest one ends.
+pub fn side_by_side<T: SingleStepDrawable + Clone>(
+    runs: &[(&str, &AlgoGen<T>)],
+) -> Vec<DrawInstruction>
+where
+    <T as SingleStepProblem>::Sol: Clone,
+{
+    let animations = runs
+        .iter()
+        .map(|(_, run)| frames_of(&run.best_frames()))
+        .collect::<Vec<_>>();
+    let widths = animations
+        .iter()
+        .map(|frames| frames.iter().map(|(world, _)| world[0]).max().unwrap_or(1))
+        .collect::<Vec<usize>>();
+    let height = animations
+        .iter()
+        .flat_map(|frames| frames.iter().map(|(world, _)| world[1]))
+        .max()
+        .unwrap_or(1);
+    let line = (height as f64 * LABEL_LINE).max(1.0);
+    let top = 2.0 * line;
+    let lefts = widths
+        .iter()
+        .scan(0, |left, width| {
+            *left += width;
+            Some((*left - width) as f64)
+        })
+        .collect::<Vec<f64>>();
+
+    let mut labels = vec![];
+    for ((name, run), left) in runs.iter().zip(lefts.iter()) {
+        labels.push(
+            Shape::Text {
+                pos: [left + 1.0, 0.0],
+                height: line * 0.8,
+                text: name.to_string(),
+                color: rgb(0.0, 0.0, 0.0),
+            }
+            .on(1),
+        );
+        labels.push(
+            Shape::Text {
+                pos: [left + 1.0, line],
+                height: line * 0.8,
+                text: format!(
+                    "{} evals, best {:.3}",
+                    run.evaluations(),
+                    run.best().get_score()
+                ),
+                color: rgb(0.3, 0.3, 0.3),
+            }
+            .on(1),
+        );
+        if *left > 0.0 {
+            labels.push(
+                Shape::Line {
+                    points: vec![[*left, 0.0], [*left, top + height as f64]],
+                    width: 0.5,
+                    color: rgb(0.2, 0.2, 0.2),
+                }
+                .on(1),
+            );
+        }
+    }
+
+    let nb_frames = animations.iter().map(|frames| frames.len()).max().unwrap_or(0);
+    let mut instructions = vec![DrawInstruction::WorldSize([
+        widths.iter().sum::<usize>(),
+        height + top.ceil() as usize,
+    ])];
+    for index in 0..nb_frames {
+        let mut shapes: Vec<Layered> = labels.clone();
+        for (frames, left) in animations.iter().zip(lefts.iter()) {
+            if let Some((_, scene)) = frames.get(index).or_else(|| frames.last()) {
+                shapes.extend(scene.iter().map(|s| s.translated([*left, top]).on(0)));
+            }
+        }
+        instructions.push(DrawInstruction::Shapes(shapes));
+    }
+    instructions
+}
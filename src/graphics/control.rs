@@ -0,0 +1,104 @@
+//! The messages between the window and the learning thread.
+//! The window sends commands, the learning thread reads them between two learning generations and
+//! sends back its events, the demos and the answers to the commands.
+use crate::config::Config;
+use crate::graphics::{learn_back, SingleStepDrawable, ToDraw};
+use crate::observer::{Event, Observer};
+use crate::problems::SingleStepProblem;
+use crate::random::GenRng;
+use std::sync::mpsc::{channel, Receiver, Sender, TryIter};
+use std::thread::{self, JoinHandle};
+
+/// What the window asks the learning thread.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Stops learning until Resume, the commands are still read.
+    Pause,
+    Resume,
+    /// Saves the network with the settings of the learning.
+    Save(String),
+    /// Changes the exploration coef, it keeps decreasing from there.
+    SetCoef(f64),
+    /// Shows the network on a new random problem, the next demos are played on it too.
+    NewDemo,
+    /// Stops the learning thread.
+    Shutdown,
+}
+
+/// What the learning thread tells the window.
+pub enum Progress {
+    Event(Event),
+    Demo(ToDraw),
+    /// The answer to a command, or why it failed.
+    Status(String),
+    /// The training is over, the thread only answers Save and Shutdown.
+    Finished,
+}
+
+/// Sends the events of the learner to the window.
+pub struct ChannelObserver {
+    progress: Sender<Progress>,
+}
+
+impl ChannelObserver {
+    pub fn new(progress: Sender<Progress>) -> Self {
+        ChannelObserver { progress }
+    }
+}
+
+impl Observer for ChannelObserver {
+    fn notify(&mut self, event: &Event) {
+        // the window may be gone already, the Shutdown will follow
+        let _ = self.progress.send(Progress::Event(event.clone()));
+    }
+}
+
+/// The window's side of a learning thread.
+/// Dropping it without shutdown leaves the thread running until it reads the Shutdown or notices
+/// the window is gone.
+pub struct Trainer {
+    commands: Sender<Command>,
+    progress: Receiver<Progress>,
+    thread: JoinHandle<()>,
+}
+
+impl Trainer {
+    /// Starts learning in a new thread, drawing the network and the problems from the generator.
+    pub fn spawn<T: SingleStepDrawable + Clone>(
+        conf: T::ProblemConfig,
+        config: Config,
+        rng: GenRng,
+    ) -> Self
+    where
+        <T as SingleStepProblem>::Sol: Clone,
+        T::ProblemConfig: 'static,
+    {
+        let (commands, commands_receiver) = channel();
+        let (progress_sender, progress) = channel();
+        let thread = thread::spawn(move || {
+            learn_back::<T>(commands_receiver, progress_sender, conf, config, rng)
+        });
+        Trainer {
+            commands,
+            progress,
+            thread,
+        }
+    }
+
+    /// Sends the command, nothing happens if the thread is over.
+    pub fn send(&self, command: Command) {
+        let _ = self.commands.send(command);
+    }
+
+    /// What the thread sent since the last call.
+    pub fn received(&self) -> TryIter<Progress> {
+        self.progress.try_iter()
+    }
+
+    /// Asks the thread to stop and waits for the end of its learning generation and for the test
+    /// score of its best network.
+    pub fn shutdown(self) {
+        self.send(Command::Shutdown);
+        let _ = self.thread.join();
+    }
+}
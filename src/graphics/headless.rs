@@ -0,0 +1,555 @@
+//! Renders the frames to files without a display : svg, png or animated gif.
+//! The encoders are written here to avoid depending on image libraries, the png is not compressed
+//! and the gif uses a fixed palette of 216 colors.
+use crate::graphics::raster::{frames_of, rasterize, to_byte, Canvas, Pixel};
+use crate::graphics::{Color, DrawInstruction, Point, Scene, Shape};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Delay between two frames of the gif in hundredths of second, like the window's timer.
+pub const GIF_DELAY: u16 = 20;
+
+/// Writes the demo to files, the format is given by the extension of the path :
+/// - out.gif : one animated gif.
+/// - out.png : one image per frame, out_000.png, out_001.png...
+/// - out.svg : one image per frame, out_000.svg, out_001.svg...
+/// Returns the files written.
+pub fn save<T: AsRef<Path>>(
+    instructions: &[DrawInstruction],
+    path: T,
+    width: usize,
+    height: usize,
+) -> io::Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    let frames = frames_of(instructions);
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("gif") => {
+            let canvases = frames
+                .iter()
+                .map(|(world, frame)| rasterize(frame, *world, width, height))
+                .collect::<Vec<Canvas>>();
+            fs::write(path, to_gif(&canvases, GIF_DELAY))?;
+            Ok(vec![path.to_path_buf()])
+        }
+        Some("png") => save_numbered(path, &frames, |(world, frame)| {
+            to_png(&rasterize(frame, *world, width, height))
+        }),
+        Some("svg") => save_numbered(path, &frames, |(world, frame)| {
+            to_svg(frame, *world, width, height).into_bytes()
+        }),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown image format : {}", path.display()),
+        )),
+    }
+}
+
+/// Writes every frame to its own file : out.png gives out_000.png, out_001.png...
+fn save_numbered<F: Fn(&([usize; 2], Scene)) -> Vec<u8>>(
+    path: &Path,
+    frames: &[([usize; 2], Scene)],
+    encode: F,
+) -> io::Result<Vec<PathBuf>> {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("frame");
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let mut written = vec![];
+    for (index, frame) in frames.iter().enumerate() {
+        let file = path.with_file_name(format!("{}_{:03}.{}", stem, index, extension));
+        fs::write(&file, encode(frame))?;
+        written.push(file);
+    }
+    Ok(written)
+}
+
+/// Draws the frame as an svg document, scaled like the window does.
+pub fn to_svg(scene: &[Shape], world_size: [usize; 2], width: usize, height: usize) -> String {
+    let (coef_x, coef_y) = (
+        width as f64 / world_size[0] as f64,
+        height as f64 / world_size[1] as f64,
+    );
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        width, height, width, height
+    );
+    for shape in scene.iter() {
+        svg.push_str(&svg_shape(&shape.scaled(coef_x, coef_y)));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The svg element of a shape given in pixels.
+fn svg_shape(shape: &Shape) -> String {
+    match shape {
+        Shape::Rect { pos, size, color } => format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>\n",
+            pos[0],
+            pos[1],
+            size[0],
+            size[1],
+            svg_fill(color)
+        ),
+        Shape::Circle {
+            center,
+            radius,
+            color,
+        } => format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>\n",
+            center[0],
+            center[1],
+            radius,
+            svg_fill(color)
+        ),
+        Shape::Line {
+            points,
+            width,
+            color,
+        } => format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-opacity=\"{}\" \
+             stroke-width=\"{}\" stroke-linejoin=\"round\"/>\n",
+            svg_points(points),
+            svg_rgb(color),
+            color[3],
+            width.max(1.0)
+        ),
+        Shape::Polygon { points, color } => format!(
+            "<polygon points=\"{}\" {}/>\n",
+            svg_points(points),
+            svg_fill(color)
+        ),
+        Shape::Text {
+            pos,
+            height,
+            text,
+            color,
+        } => format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" {}>{}</text>\n",
+            pos[0],
+            pos[1] + height,
+            height * 1.4,
+            svg_fill(color),
+            text.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+        ),
+    }
+}
+
+fn svg_rgb(color: &Color) -> String {
+    format!(
+        "rgb({},{},{})",
+        to_byte(color[0]),
+        to_byte(color[1]),
+        to_byte(color[2])
+    )
+}
+
+fn svg_fill(color: &Color) -> String {
+    format!("fill=\"{}\" fill-opacity=\"{}\"", svg_rgb(color), color[3])
+}
+
+fn svg_points(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", p[0], p[1]))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Encodes the canvas as a png without compression.
+pub fn to_png(canvas: &Canvas) -> Vec<u8> {
+    let mut raw = Vec::with_capacity((canvas.width * 3 + 1) * canvas.height);
+    for y in 0..canvas.height {
+        // no filter for this row
+        raw.push(0);
+        for x in 0..canvas.width {
+            raw.extend_from_slice(&canvas.get(x, y));
+        }
+    }
+    let mut header = vec![];
+    header.extend_from_slice(&(canvas.width as u32).to_be_bytes());
+    header.extend_from_slice(&(canvas.height as u32).to_be_bytes());
+    // 8 bits per channel, rgb, default compression, filter and no interlace.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    png_chunk(&mut png, b"IHDR", &header);
+    png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps the data in a zlib stream made of uncompressed blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(65535).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 == 1 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+    !data.iter().fold(0xFFFF_FFFFu32, |crc, &b| {
+        table[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data.iter() {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// Index of the color in the 6x6x6 color cube used as the gif palette.
+fn palette_index(color: Pixel) -> u8 {
+    let level = |c: u8| ((c as f64 / 255.0) * 5.0).round() as u8;
+    level(color[0]) * 36 + level(color[1]) * 6 + level(color[2])
+}
+
+/// Encodes the canvases as an animated gif looping forever, the delay is in hundredths of second.
+/// All the canvases must have the size of the first one.
+pub fn to_gif(canvases: &[Canvas], delay: u16) -> Vec<u8> {
+    let (width, height) = canvases
+        .first()
+        .map(|c| (c.width as u16, c.height as u16))
+        .unwrap_or((1, 1));
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend_from_slice(&width.to_le_bytes());
+    gif.extend_from_slice(&height.to_le_bytes());
+    // global color table of 256 colors, background is the first color.
+    gif.extend_from_slice(&[0xF7, 0, 0]);
+    for index in 0..256usize {
+        let level = |l: usize| (l * 51) as u8;
+        match index < 216 {
+            true => gif.extend_from_slice(&[level(index / 36), level(index / 6 % 6), level(index % 6)]),
+            false => gif.extend_from_slice(&[0, 0, 0]),
+        }
+    }
+    // loop forever
+    gif.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    gif.extend_from_slice(b"NETSCAPE2.0");
+    gif.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+    for canvas in canvases.iter() {
+        gif.extend_from_slice(&[0x21, 0xF9, 0x04, 0x00]);
+        gif.extend_from_slice(&delay.to_le_bytes());
+        gif.extend_from_slice(&[0x00, 0x00]);
+        gif.push(0x2C);
+        gif.extend_from_slice(&[0, 0, 0, 0]);
+        gif.extend_from_slice(&(canvas.width as u16).to_le_bytes());
+        gif.extend_from_slice(&(canvas.height as u16).to_le_bytes());
+        gif.push(0x00);
+        let indices = canvas
+            .pixels
+            .iter()
+            .map(|&p| palette_index(p))
+            .collect::<Vec<u8>>();
+        gif.push(8);
+        for block in lzw(&indices).chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0x00);
+    }
+    gif.push(0x3B);
+    gif
+}
+
+/// Writes the codes with a variable number of bits, least significant bit first.
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u32,
+    nb_bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.bits |= (code as u32) << self.nb_bits;
+        self.nb_bits += size;
+        while self.nb_bits >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.nb_bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.nb_bits > 0 {
+            self.out.push(self.bits as u8);
+        }
+        self.out
+    }
+}
+
+/// Compresses the palette indices with the gif variant of lzw, 8 bits minimum code size.
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    const MAX_CODE: u16 = 4096;
+    let mut writer = BitWriter {
+        out: vec![],
+        bits: 0,
+        nb_bits: 0,
+    };
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = END + 1;
+    let mut size = 9;
+    writer.write(CLEAR, size);
+    let mut iter = indices.iter();
+    let mut prefix = match iter.next() {
+        Some(&first) => first as u16,
+        None => {
+            writer.write(END, size);
+            return writer.finish();
+        }
+    };
+    for &k in iter {
+        if let Some(&code) = dict.get(&(prefix, k)) {
+            prefix = code;
+            continue;
+        }
+        writer.write(prefix, size);
+        if next_code < MAX_CODE {
+            dict.insert((prefix, k), next_code);
+            next_code += 1;
+            // the reader only learns this code with the next one, it widens one code later
+            if next_code > 1 << size && size < 12 {
+                size += 1;
+            }
+        } else {
+            writer.write(CLEAR, size);
+            dict.clear();
+            next_code = END + 1;
+            size = 9;
+        }
+        prefix = k as u16;
+    }
+    writer.write(prefix, size);
+    writer.write(END, size);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_known_vector() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn adler32_known_vector() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    fn be32(bytes: &[u8]) -> u32 {
+        bytes.iter().fold(0, |n, &b| n << 8 | b as u32)
+    }
+
+    fn le16(bytes: &[u8]) -> u16 {
+        bytes[0] as u16 | (bytes[1] as u16) << 8
+    }
+
+    /// Reads the codes back, least significant bit first.
+    struct BitReader<'a> {
+        data: &'a [u8],
+        pos: usize,
+    }
+
+    impl<'a> BitReader<'a> {
+        fn read(&mut self, size: usize) -> Option<u16> {
+            if self.pos + size > self.data.len() * 8 {
+                return None;
+            }
+            let mut code = 0;
+            for i in 0..size {
+                let bit = self.data[(self.pos + i) / 8] >> ((self.pos + i) % 8) & 1;
+                code |= (bit as u16) << i;
+            }
+            self.pos += size;
+            Some(code)
+        }
+    }
+
+    /// Decodes the lzw stream like a gif reader.
+    /// Returns the indices, the number of clear codes and the widest code read.
+    fn unlzw(data: &[u8]) -> (Vec<u8>, usize, usize) {
+        let mut reader = BitReader { data, pos: 0 };
+        let (mut out, mut clears, mut widest) = (vec![], 0, 0);
+        let mut dict: Vec<Vec<u8>> = vec![];
+        let mut prev: Option<Vec<u8>> = None;
+        let mut size = 9;
+        while let Some(code) = reader.read(size) {
+            widest = widest.max(size);
+            match code {
+                256 => {
+                    dict = (0..=255u8).map(|i| vec![i]).collect();
+                    dict.extend(vec![vec![], vec![]]);
+                    prev = None;
+                    size = 9;
+                    clears += 1;
+                    continue;
+                }
+                257 => break,
+                _ => (),
+            }
+            let entry = match dict.get(code as usize) {
+                Some(entry) => entry.clone(),
+                None => {
+                    assert_eq!(code as usize, dict.len(), "unknown code");
+                    let mut entry = prev.clone().unwrap();
+                    entry.push(entry[0]);
+                    entry
+                }
+            };
+            out.extend_from_slice(&entry);
+            if let Some(mut previous) = prev {
+                if dict.len() < 4096 {
+                    previous.push(entry[0]);
+                    dict.push(previous);
+                }
+            }
+            if dict.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+            prev = Some(entry);
+        }
+        (out, clears, widest)
+    }
+
+    #[test]
+    fn lzw_round_trips_small_inputs() {
+        for indices in [vec![], vec![7], vec![1, 1, 1, 1, 2, 1, 1, 1, 1, 2]].iter() {
+            let (decoded, clears, widest) = unlzw(&lzw(indices));
+            assert_eq!(&decoded, indices);
+            assert_eq!((clears, widest), (1, 9));
+        }
+    }
+
+    #[test]
+    fn lzw_round_trips_through_every_code_width_and_clear() {
+        let mut state = 12345u32;
+        let indices = (0..100_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state % 216) as u8
+            })
+            .collect::<Vec<u8>>();
+        let (decoded, clears, widest) = unlzw(&lzw(&indices));
+        assert!(decoded == indices);
+        assert_eq!(widest, 12);
+        assert!(clears > 1, "the dictionary was never cleared");
+    }
+
+    /// A white 2x2 canvas with a red pixel at the top right.
+    fn small_canvas() -> Canvas {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.set(1, 0, [255, 0, 0]);
+        canvas
+    }
+
+    #[test]
+    fn png_has_its_header_and_chunks() {
+        let png = to_png(&small_canvas());
+        assert_eq!(&png[..8], &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+        let mut chunks = vec![];
+        let mut pos = 8;
+        while pos < png.len() {
+            let len = be32(&png[pos..pos + 4]) as usize;
+            let kind = &png[pos + 4..pos + 8];
+            let data = &png[pos + 8..pos + 8 + len];
+            let crc = be32(&png[pos + 8 + len..pos + 12 + len]);
+            assert_eq!(crc, crc32(&png[pos + 4..pos + 8 + len]));
+            chunks.push((kind.to_vec(), data.to_vec()));
+            pos += 12 + len;
+        }
+        assert_eq!(pos, png.len());
+        let kinds = chunks.iter().map(|c| c.0.clone()).collect::<Vec<Vec<u8>>>();
+        assert_eq!(
+            kinds,
+            vec![b"IHDR".to_vec(), b"IDAT".to_vec(), b"IEND".to_vec()]
+        );
+        assert_eq!(chunks[0].1, vec![0, 0, 0, 2, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        let rows = vec![0, 255, 255, 255, 255, 0, 0, 0, 255, 255, 255, 255, 255, 255];
+        let idat = &chunks[1].1;
+        assert_eq!(&idat[..2], &[0x78, 0x01]);
+        // one final stored block
+        assert_eq!(idat[2], 1);
+        assert_eq!(le16(&idat[3..5]) as usize, rows.len());
+        assert_eq!(le16(&idat[5..7]), !(rows.len() as u16));
+        assert_eq!(&idat[7..7 + rows.len()], &rows[..]);
+        assert_eq!(be32(&idat[7 + rows.len()..]), adler32(&rows));
+        assert!(chunks[2].1.is_empty());
+    }
+
+    #[test]
+    fn gif_has_its_header_and_blocks() {
+        let canvas = small_canvas();
+        let gif = to_gif(&[canvas.clone(), canvas], GIF_DELAY);
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!((le16(&gif[6..8]), le16(&gif[8..10])), (2, 2));
+        assert_eq!(&gif[10..13], &[0xF7, 0, 0]);
+        let palette = &gif[13..13 + 768];
+        assert_eq!(&palette[3 * 180..3 * 181], &[255, 0, 0]);
+        assert_eq!(&palette[3 * 215..3 * 216], &[255, 255, 255]);
+        let mut pos = 13 + 768;
+        assert_eq!(&gif[pos..pos + 3], &[0x21, 0xFF, 0x0B]);
+        assert_eq!(&gif[pos + 3..pos + 14], b"NETSCAPE2.0");
+        pos += 19;
+        for _ in 0..2 {
+            assert_eq!(&gif[pos..pos + 4], &[0x21, 0xF9, 0x04, 0x00]);
+            assert_eq!(le16(&gif[pos + 4..pos + 6]), GIF_DELAY);
+            pos += 8;
+            assert_eq!(gif[pos], 0x2C);
+            assert_eq!(&gif[pos + 1..pos + 10], &[0, 0, 0, 0, 2, 0, 2, 0, 0]);
+            assert_eq!(gif[pos + 10], 8);
+            pos += 11;
+            let mut data = vec![];
+            while gif[pos] != 0 {
+                let len = gif[pos] as usize;
+                data.extend_from_slice(&gif[pos + 1..pos + 1 + len]);
+                pos += 1 + len;
+            }
+            pos += 1;
+            assert_eq!(unlzw(&data).0, vec![215, 180, 215, 215]);
+        }
+        assert_eq!(&gif[pos..], &[0x3B]);
+    }
+}
@@ -1,24 +1,58 @@
 pub mod app;
-use crate::algogen::AlgoGen;
-use crate::params::*;
+pub mod charts;
+pub mod compare;
+pub mod control;
+pub mod headless;
+pub mod raster;
+pub mod settings;
+pub mod shape;
+pub mod terminal;
+use crate::algogen::{AlgoGen, AlgoGenConf};
+use crate::config::Config;
+use crate::netfile::{self, NetworkMeta};
+use crate::observer::{ConsoleObserver, ObserverHandle, ObserverList};
 use crate::problems;
 use crate::problems::ManyStepProblem;
 use crate::problems::SingleStepProblem;
-use crate::reilearn::{LearnParams, ReiLearn};
+use crate::random::{self, GenRng};
+use crate::reilearn::ReiLearn;
+use crate::trace::Trace;
+pub use self::shape::{rgb, rgba, Color, Layered, Point, Shape};
+use self::control::{ChannelObserver, Command, Progress};
 use lmsmw::network::Network;
-use rand::prelude::thread_rng;
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
 
-pub struct ToDraw(Vec<DrawInstruction>);
+/// The latest demo : its frames, the decisions taken during it and the frames comparing its best
+/// solution to the baseline's.
+pub struct ToDraw {
+    pub frames: Vec<DrawInstruction>,
+    pub decisions: Trace,
+    pub comparison: Vec<DrawInstruction>,
+}
+
+impl ToDraw {
+    pub fn new() -> Self {
+        ToDraw {
+            frames: Vec::new(),
+            decisions: Trace::new(),
+            comparison: Vec::new(),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum DrawInstruction {
     Frame(Vec<Entity>),
+    /// A frame made of any shapes.
+    Shapes(Vec<Layered>),
     WorldSize([usize; 2]),
 }
 
 pub type Frame = Vec<Entity>;
+/// A rectangle : x, y, width, height, red, green, blue.
 pub type Entity = [f64; 7];
+/// The shapes of a frame, ready to be drawn in this order.
+pub type Scene = Vec<Shape>;
 
 pub trait SingleStepDrawable: SingleStepProblem {
     fn get_frames(&self, sol: &Self::Sol) -> Vec<DrawInstruction>;
@@ -28,34 +62,142 @@ pub trait ManyStepDrawable: ManyStepProblem {
     fn get_frames(&self) -> Vec<DrawInstruction>;
 }
 
-/// Creates a network, making it learn to supervise genetic algorithms and print its score on a
-/// set of examples.
+/// Creates a network and trains it to supervise genetic algorithms, keeping the one with the best
+/// validation score and scoring it on the test problems at the end of the training.
+/// Reads the commands of the window between two learning generations and sends it the events and
+/// a demo of every generation. Once the training is over the best network can still be saved,
+/// until Shutdown or until the window is gone.
 pub fn learn_back<T: SingleStepDrawable + Clone>(
-    next: Arc<Mutex<ToDraw>>,
-    conf: Arc<Mutex<T::ProblemConfig>>,
+    commands: Receiver<Command>,
+    progress: Sender<Progress>,
+    conf: T::ProblemConfig,
+    config: Config,
+    mut rng: GenRng,
 ) where
     <T as problems::SingleStepProblem>::Sol: std::clone::Clone,
 {
-    let learn_params = LearnParams::new(
-        NB_EXAMPLE_PROBLEMS,
-        TESTS_PER_PROBLEM,
-        MAX_GEN,
-        STARTING_COEF,
-        COEF_MODIFICATOR,
-        PERCENT_ELITE,
+    let net = Network::new(config.network.layers(&config.learning.history), &mut rng);
+    let algo_conf = AlgoGenConf {
+        problem: conf,
+        settings: config.algogen.clone(),
+    };
+    let mut rl = ReiLearn::<AlgoGen<T>>::with_rng(
+        net,
+        algo_conf,
+        config.learning.clone(),
+        random::derive(&mut rng),
     );
-    let mut random = thread_rng();
-    let layers = layers![15, 40, 10, 6];
-    let net = Network::new(layers, &mut random);
-    let mut rl = ReiLearn::<AlgoGen<T>>::new(net, conf.lock().unwrap().clone(), learn_params);
-    loop {
-        next.lock().unwrap().0 = rl.get_frames();
-        println!(
-            "score on test data with network : {}",
-            rl.run_on_test_example() / (TEST_DATA_SIZE as f64)
-        );
-        rl.next_gen();
-        // oh god yes
-        // thank you !!!
+    rl.set_observer(ObserverHandle::new(ObserverList(vec![
+        Box::new(ConsoleObserver::new()),
+        Box::new(ChannelObserver::new(progress.clone())),
+    ])));
+    rl.run_baseline();
+    let mut demo_problem = rl.get_validation_problems()[0].clone();
+    if progress
+        .send(Progress::Demo(demo_of(&rl, &demo_problem)))
+        .is_err()
+    {
+        return;
+    }
+    let mut paused = false;
+    let mut shutdown = false;
+    let report = rl.train(|rl, _, _| {
+        loop {
+            let command = match paused {
+                true => commands.recv().map_err(|_| TryRecvError::Disconnected),
+                false => commands.try_recv(),
+            };
+            let status = match command {
+                Ok(Command::Pause) => {
+                    paused = true;
+                    String::from("learning paused")
+                }
+                Ok(Command::Resume) => {
+                    paused = false;
+                    String::from("learning resumed")
+                }
+                Ok(Command::Save(path)) => save_best(rl, &config, None, &path),
+                Ok(Command::SetCoef(coef)) => {
+                    rl.coef = coef;
+                    format!("exploration coef set to {}", coef)
+                }
+                Ok(Command::NewDemo) => {
+                    demo_problem = rl.random_problem();
+                    let _ = progress.send(Progress::Demo(demo_of(rl, &demo_problem)));
+                    String::from("demo on a new problem")
+                }
+                Ok(Command::Shutdown) | Err(TryRecvError::Disconnected) => {
+                    shutdown = true;
+                    rl.stop_training();
+                    return;
+                }
+                Err(TryRecvError::Empty) => break,
+            };
+            let _ = progress.send(Progress::Status(status));
+        }
+        if progress
+            .send(Progress::Demo(demo_of(rl, &demo_problem)))
+            .is_err()
+        {
+            shutdown = true;
+            rl.stop_training();
+        }
+    });
+    let _ = progress.send(Progress::Finished);
+    let _ = progress.send(Progress::Status(format!(
+        "kept the network of validation score {} after {} generations, test score : {}",
+        report.best_validation, report.generations, report.test_score
+    )));
+    while !shutdown {
+        let status = match commands.recv() {
+            Ok(Command::Save(path)) => save_best(&rl, &config, Some(report.test_score), &path),
+            Ok(Command::Shutdown) | Err(_) => break,
+            Ok(_) => String::from("the learning is over, the network can only be saved"),
+        };
+        let _ = progress.send(Progress::Status(status));
+    }
+}
+
+/// Saves the network with the best validation score and that score, with its test score once
+/// it is known.
+fn save_best<T: SingleStepDrawable + Clone>(
+    rl: &ReiLearn<AlgoGen<T>>,
+    config: &Config,
+    test_score: Option<f64>,
+    path: &str,
+) -> String
+where
+    <T as problems::SingleStepProblem>::Sol: std::clone::Clone,
+{
+    let (net, validation) = match rl.best_network() {
+        Some(best) => best,
+        None => return String::from("no network to save yet"),
+    };
+    let meta = NetworkMeta {
+        problem: config.problem.name.clone(),
+        config: config.clone(),
+        best_score: test_score,
+        validation_score: Some(validation),
+    };
+    match netfile::save(net, &meta, path) {
+        Ok(()) => format!(
+            "best network saved to {}, validation score : {}",
+            path, validation
+        ),
+        Err(e) => e,
+    }
+}
+
+/// Lets the network and the baseline play the problem.
+fn demo_of<T: SingleStepDrawable + Clone>(rl: &ReiLearn<AlgoGen<T>>, problem: &AlgoGen<T>) -> ToDraw
+where
+    <T as problems::SingleStepProblem>::Sol: std::clone::Clone,
+{
+    let (frames, demo) = rl.record_demo_on(problem);
+    let baseline = rl.baseline_demo_on(problem);
+    ToDraw {
+        frames,
+        decisions: demo.trace().clone(),
+        comparison: compare::side_by_side(&[("network", &demo), ("fixed parameters", &baseline)]),
     }
 }
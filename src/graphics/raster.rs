@@ -0,0 +1,274 @@
+//! Draws the frames on a grid of pixels, without gtk.
+//! Used by the headless and terminal renderers.
+use crate::graphics::shape::ordered;
+use crate::graphics::{Color, DrawInstruction, Point, Scene, Shape};
+
+/// An rgb color with values between 0 and 255.
+pub type Pixel = [u8; 3];
+
+pub const WHITE: Pixel = [255, 255, 255];
+
+/// Width and height of a character of the font, in font pixels.
+pub const GLYPH_SIZE: [usize; 2] = [3, 5];
+
+/// A grid of pixels, row after row.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Pixel>,
+}
+
+impl Canvas {
+    /// Creates a white canvas.
+    pub fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![WHITE; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Pixel {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Sets a pixel, ignoring the pixels out of the canvas.
+    pub fn set(&mut self, x: i64, y: i64, color: Pixel) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.pixels[y as usize * self.width + x as usize] = color;
+        }
+    }
+
+    /// Paints the color over the pixel, mixing them with the color's opacity.
+    pub fn blend(&mut self, x: i64, y: i64, color: Color) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let alpha = color[3].max(0.0).min(1.0);
+        let old = self.get(x as usize, y as usize);
+        let mix = |i: usize| to_byte(color[i] * alpha + old[i] as f64 / 255.0 * (1.0 - alpha));
+        self.set(x, y, [mix(0), mix(1), mix(2)]);
+    }
+
+    /// Fills the rectangle given in pixels, at least one pixel is filled in each direction.
+    pub fn fill_rect(&mut self, x: f64, y: f64, size_x: f64, size_y: f64, color: Color) {
+        let (x0, y0) = (x.round() as i64, y.round() as i64);
+        let x1 = ((x + size_x).round() as i64).max(x0 + 1);
+        let y1 = ((y + size_y).round() as i64).max(y0 + 1);
+        for py in y0.max(0)..y1.min(self.height as i64) {
+            for px in x0.max(0)..x1.min(self.width as i64) {
+                self.blend(px, py, color);
+            }
+        }
+    }
+
+    /// Fills the pixels whose center is in the circle, at least the one of the center.
+    pub fn fill_circle(&mut self, center: Point, radius: f64, color: Color) {
+        let (x0, x1) = ((center[0] - radius).floor() as i64, (center[0] + radius).ceil() as i64);
+        let (y0, y1) = ((center[1] - radius).floor() as i64, (center[1] + radius).ceil() as i64);
+        let mut filled = false;
+        for py in y0.max(0)..y1.min(self.height as i64) {
+            for px in x0.max(0)..x1.min(self.width as i64) {
+                let (dx, dy) = (px as f64 + 0.5 - center[0], py as f64 + 0.5 - center[1]);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.blend(px, py, color);
+                    filled = true;
+                }
+            }
+        }
+        if !filled {
+            self.blend(center[0] as i64, center[1] as i64, color);
+        }
+    }
+
+    /// Fills the pixels whose center is inside the polygon, using the even-odd rule.
+    pub fn fill_polygon(&mut self, points: &[Point], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+        for py in 0..self.height as i64 {
+            let y = py as f64 + 0.5;
+            let mut crossings = vec![];
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if (a[1] <= y) != (b[1] <= y) {
+                    crossings.push(a[0] + (y - a[1]) / (b[1] - a[1]) * (b[0] - a[0]));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            for pair in crossings.chunks(2) {
+                if let [start, end] = pair {
+                    let first = (start - 0.5).ceil().max(0.0) as i64;
+                    let last = (end - 0.5).floor().min(self.width as f64 - 1.0) as i64;
+                    for px in first..=last {
+                        self.blend(px, py, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws every segment as a thin polygon, at least one pixel wide.
+    pub fn draw_line(&mut self, points: &[Point], width: f64, color: Color) {
+        let half = width.max(1.0) / 2.0;
+        for segment in points.windows(2) {
+            let (a, b) = (segment[0], segment[1]);
+            let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+            let len = (dx * dx + dy * dy).sqrt();
+            if len == 0.0 {
+                continue;
+            }
+            // perpendicular of the segment with the length of half the width
+            let (nx, ny) = (-dy / len * half, dx / len * half);
+            self.fill_polygon(
+                &[
+                    [a[0] + nx, a[1] + ny],
+                    [b[0] + nx, b[1] + ny],
+                    [b[0] - nx, b[1] - ny],
+                    [a[0] - nx, a[1] - ny],
+                ],
+                color,
+            );
+        }
+        if points.len() == 1 {
+            self.fill_circle(points[0], half, color);
+        }
+    }
+
+    /// Writes the text with the tiny font, the height is the one of a capital letter.
+    pub fn draw_text(&mut self, pos: Point, height: f64, text: &str, color: Color) {
+        let cell = (height / GLYPH_SIZE[1] as f64).max(1.0);
+        for (index, c) in text.chars().enumerate() {
+            let left = pos[0] + (index * (GLYPH_SIZE[0] + 1)) as f64 * cell;
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for col in 0..GLYPH_SIZE[0] {
+                    if bits & (4 >> col) != 0 {
+                        self.fill_rect(
+                            left + col as f64 * cell,
+                            pos[1] + row as f64 * cell,
+                            cell,
+                            cell,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn draw(&mut self, shape: &Shape) {
+        match shape {
+            Shape::Rect { pos, size, color } => {
+                self.fill_rect(pos[0], pos[1], size[0], size[1], *color)
+            }
+            Shape::Circle {
+                center,
+                radius,
+                color,
+            } => self.fill_circle(*center, *radius, *color),
+            Shape::Line {
+                points,
+                width,
+                color,
+            } => self.draw_line(points, *width, *color),
+            Shape::Polygon { points, color } => self.fill_polygon(points, *color),
+            Shape::Text {
+                pos,
+                height,
+                text,
+                color,
+            } => self.draw_text(*pos, *height, text, *color),
+        }
+    }
+}
+
+/// The rows of a character, the left pixel is the bit 4.
+/// Lower case letters are drawn as capitals and the unknown characters as '?'.
+pub fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0, 0, 0, 0, 0],
+        '0' => [7, 5, 5, 5, 7],
+        '1' => [2, 6, 2, 2, 7],
+        '2' => [7, 1, 7, 4, 7],
+        '3' => [7, 1, 7, 1, 7],
+        '4' => [5, 5, 7, 1, 1],
+        '5' => [7, 4, 7, 1, 7],
+        '6' => [7, 4, 7, 5, 7],
+        '7' => [7, 1, 1, 1, 1],
+        '8' => [7, 5, 7, 5, 7],
+        '9' => [7, 5, 7, 1, 7],
+        'A' => [2, 5, 7, 5, 5],
+        'B' => [6, 5, 6, 5, 6],
+        'C' => [3, 4, 4, 4, 3],
+        'D' => [6, 5, 5, 5, 6],
+        'E' => [7, 4, 6, 4, 7],
+        'F' => [7, 4, 6, 4, 4],
+        'G' => [3, 4, 5, 5, 3],
+        'H' => [5, 5, 7, 5, 5],
+        'I' => [7, 2, 2, 2, 7],
+        'J' => [1, 1, 1, 5, 2],
+        'K' => [5, 5, 6, 5, 5],
+        'L' => [4, 4, 4, 4, 7],
+        'M' => [5, 7, 7, 5, 5],
+        'N' => [6, 5, 5, 5, 5],
+        'O' => [2, 5, 5, 5, 2],
+        'P' => [6, 5, 6, 4, 4],
+        'Q' => [2, 5, 5, 6, 3],
+        'R' => [6, 5, 6, 5, 5],
+        'S' => [3, 4, 2, 1, 6],
+        'T' => [7, 2, 2, 2, 2],
+        'U' => [5, 5, 5, 5, 7],
+        'V' => [5, 5, 5, 5, 2],
+        'W' => [5, 5, 7, 7, 5],
+        'X' => [5, 5, 2, 5, 5],
+        'Y' => [5, 5, 2, 2, 2],
+        'Z' => [7, 1, 2, 4, 7],
+        '.' => [0, 0, 0, 0, 2],
+        ',' => [0, 0, 0, 2, 4],
+        ':' => [0, 2, 0, 2, 0],
+        '-' => [0, 0, 7, 0, 0],
+        '+' => [0, 2, 7, 2, 0],
+        '=' => [0, 7, 0, 7, 0],
+        '/' => [1, 1, 2, 4, 4],
+        '(' => [1, 2, 2, 2, 1],
+        ')' => [4, 2, 2, 2, 4],
+        '%' => [5, 1, 2, 4, 5],
+        '_' => [0, 0, 0, 0, 7],
+        '!' => [2, 2, 2, 0, 2],
+        _ => [7, 1, 2, 0, 2],
+    }
+}
+
+/// Converts a color component between 0 and 1.
+pub fn to_byte(c: f64) -> u8 {
+    (c.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+/// Follows the instructions like App::draw_instruction : the world size applies to the frames
+/// after it, and the default world is 1x1.
+/// Returns every frame with the world size it must be drawn with.
+pub fn frames_of(instructions: &[DrawInstruction]) -> Vec<([usize; 2], Scene)> {
+    let mut world_size = [1, 1];
+    let mut frames = vec![];
+    for instruction in instructions.iter() {
+        match instruction {
+            DrawInstruction::WorldSize(size) => world_size = *size,
+            frame => frames.extend(ordered(frame).map(|scene| (world_size, scene))),
+        }
+    }
+    frames
+}
+
+/// Draws the frame on a white canvas of the given size, scaling the world like the window does.
+pub fn rasterize(scene: &[Shape], world_size: [usize; 2], width: usize, height: usize) -> Canvas {
+    let mut canvas = Canvas::new(width, height);
+    let (coef_x, coef_y) = (
+        width as f64 / world_size[0] as f64,
+        height as f64 / world_size[1] as f64,
+    );
+    for shape in scene.iter() {
+        canvas.draw(&shape.scaled(coef_x, coef_y));
+    }
+    canvas
+}
@@ -0,0 +1,120 @@
+//! The widgets choosing the settings of the learning shown in the window.
+//! The learning restarts with the settings read from them.
+use crate::config::{Config, NetworkSettings};
+use gtk::prelude::*;
+use gtk::{Button, ComboBoxText, Entry, Label, SpinButton};
+
+#[derive(Clone)]
+pub struct SettingsView {
+    pub container: gtk::Box,
+    /// A problem or a mixture, the known problems are in the list.
+    problem: ComboBoxText,
+    size: SpinButton,
+    nb_problems: SpinButton,
+    test_per_prob: SpinButton,
+    percent_elite: SpinButton,
+    starting_coef: SpinButton,
+    coef_mod: SpinButton,
+    hidden: Entry,
+    pub restart_button: Button,
+    status: Label,
+}
+
+impl SettingsView {
+    pub fn new() -> Self {
+        let spin = |min: f64, max: f64, step: f64, digits: u32| {
+            let spin = SpinButton::new_with_range(min, max, step);
+            spin.set_digits(digits);
+            spin
+        };
+        let view = SettingsView {
+            container: gtk::Box::new(gtk::Orientation::Vertical, 2),
+            problem: ComboBoxText::new_with_entry(),
+            size: spin(1.0, 1000.0, 1.0, 0),
+            nb_problems: spin(1.0, 10000.0, 1.0, 0),
+            test_per_prob: spin(2.0, 1000.0, 1.0, 0),
+            percent_elite: spin(0.01, 0.5, 0.01, 2),
+            starting_coef: spin(0.0, 10.0, 0.1, 2),
+            coef_mod: spin(0.01, 1.0, 0.01, 2),
+            hidden: Entry::new(),
+            restart_button: Button::new_with_label("Restart learning"),
+            status: Label::new(None),
+        };
+        view.pack();
+        view
+    }
+
+    fn pack(&self) {
+        let rows: Vec<(&str, &gtk::Widget)> = vec![
+            ("problem", self.problem.upcast_ref()),
+            ("size", self.size.upcast_ref()),
+            ("problems per generation", self.nb_problems.upcast_ref()),
+            ("playouts per problem", self.test_per_prob.upcast_ref()),
+            ("percent elite", self.percent_elite.upcast_ref()),
+            ("starting coef", self.starting_coef.upcast_ref()),
+            ("coef modificator", self.coef_mod.upcast_ref()),
+            ("hidden layers", self.hidden.upcast_ref()),
+        ];
+        for (name, widget) in rows {
+            self.container
+                .pack_start(&Label::new(Some(name)), false, false, 0);
+            self.container.pack_start(widget, false, false, 0);
+        }
+        self.container
+            .pack_start(&self.restart_button, false, false, 0);
+        self.status.set_line_wrap(true);
+        self.container.pack_start(&self.status, false, false, 0);
+    }
+
+    /// Adds the problems to the list, any other problem or mixture can still be typed.
+    pub fn add_problems(&self, problems: &[String]) {
+        for name in problems.iter() {
+            self.problem.append_text(name);
+        }
+    }
+
+    /// Shows the settings of the config.
+    pub fn fill(&self, config: &Config) {
+        if let Some(entry) = self
+            .problem
+            .get_child()
+            .and_then(|c| c.downcast::<Entry>().ok())
+        {
+            entry.set_text(&config.problem.name);
+        }
+        self.size.set_value(config.problem.size as f64);
+        let l = &config.learning;
+        self.nb_problems.set_value(l.nb_problems as f64);
+        self.test_per_prob.set_value(l.test_per_prob as f64);
+        self.percent_elite.set_value(l.percent_elite);
+        self.starting_coef.set_value(l.starting_coef);
+        self.coef_mod.set_value(l.coef_mod);
+        let hidden = config
+            .network
+            .hidden
+            .iter()
+            .map(|h| h.to_string())
+            .collect::<Vec<String>>();
+        self.hidden.set_text(&hidden.join(","));
+    }
+
+    /// Returns the config with the values of the widgets, the other values are taken from base.
+    pub fn read(&self, base: &Config) -> Result<Config, String> {
+        let mut config = base.clone();
+        config.problem.name = self.problem.get_active_text().unwrap_or_default();
+        config.problem.size = self.size.get_value_as_int() as usize;
+        config.learning.nb_problems = self.nb_problems.get_value_as_int() as usize;
+        config.learning.test_per_prob = self.test_per_prob.get_value_as_int() as usize;
+        config.learning.percent_elite = self.percent_elite.get_value();
+        config.learning.starting_coef = self.starting_coef.get_value();
+        config.learning.coef_mod = self.coef_mod.get_value();
+        config.network.hidden =
+            NetworkSettings::parse_hidden(&self.hidden.get_text().unwrap_or_default())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn set_status(&self, status: &str) {
+        self.status.set_text(status);
+    }
+}
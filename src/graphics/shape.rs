@@ -0,0 +1,141 @@
+//! The shapes a frame can be made of, in world coordinates.
+//! The old rectangle entities are still accepted and become rectangles on the layer 0.
+use crate::graphics::{DrawInstruction, Entity};
+
+/// A point in the world, x goes right and y goes down.
+pub type Point = [f64; 2];
+
+/// Red, green, blue and opacity, between 0 and 1.
+pub type Color = [f64; 4];
+
+pub fn rgb(red: f64, green: f64, blue: f64) -> Color {
+    [red, green, blue, 1.0]
+}
+
+pub fn rgba(red: f64, green: f64, blue: f64, alpha: f64) -> Color {
+    [red, green, blue, alpha]
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Rect {
+        pos: Point,
+        size: Point,
+        color: Color,
+    },
+    Circle {
+        center: Point,
+        radius: f64,
+        color: Color,
+    },
+    /// A line going through all the points.
+    Line {
+        points: Vec<Point>,
+        width: f64,
+        color: Color,
+    },
+    /// A filled polygon, closed between the last and the first point.
+    Polygon {
+        points: Vec<Point>,
+        color: Color,
+    },
+    /// A label, pos is its upper left corner.
+    Text {
+        pos: Point,
+        height: f64,
+        text: String,
+        color: Color,
+    },
+}
+
+/// A shape and the layer it is drawn on, the higher layers are drawn over the lower ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layered {
+    pub layer: i32,
+    pub shape: Shape,
+}
+
+impl Shape {
+    pub fn on(self, layer: i32) -> Layered {
+        Layered { layer, shape: self }
+    }
+
+    pub fn from_entity(&[x, y, size_x, size_y, red, green, blue]: &Entity) -> Self {
+        Shape::Rect {
+            pos: [x, y],
+            size: [size_x, size_y],
+            color: rgb(red, green, blue),
+        }
+    }
+
+    /// The same shape in pixels, the lengths that do not have a direction (radius, width) use the
+    /// smallest coefficient.
+    pub fn scaled(&self, coef_x: f64, coef_y: f64) -> Shape {
+        self.transformed([coef_x, coef_y], [0.0, 0.0])
+    }
+
+    /// The same shape moved by the offset.
+    pub fn translated(&self, offset: Point) -> Shape {
+        self.transformed([1.0, 1.0], offset)
+    }
+
+    /// Scales the shape then moves it.
+    fn transformed(&self, [coef_x, coef_y]: Point, offset: Point) -> Shape {
+        let point = |p: &Point| [p[0] * coef_x + offset[0], p[1] * coef_y + offset[1]];
+        let coef = coef_x.min(coef_y);
+        match self {
+            Shape::Rect { pos, size, color } => Shape::Rect {
+                pos: point(pos),
+                size: [size[0] * coef_x, size[1] * coef_y],
+                color: *color,
+            },
+            Shape::Circle {
+                center,
+                radius,
+                color,
+            } => Shape::Circle {
+                center: point(center),
+                radius: radius * coef,
+                color: *color,
+            },
+            Shape::Line {
+                points,
+                width,
+                color,
+            } => Shape::Line {
+                points: points.iter().map(point).collect(),
+                width: width * coef,
+                color: *color,
+            },
+            Shape::Polygon { points, color } => Shape::Polygon {
+                points: points.iter().map(point).collect(),
+                color: *color,
+            },
+            Shape::Text {
+                pos,
+                height,
+                text,
+                color,
+            } => Shape::Text {
+                pos: point(pos),
+                height: height * coef_y,
+                text: text.clone(),
+                color: *color,
+            },
+        }
+    }
+}
+
+/// Returns the shapes of a frame in the order they must be drawn.
+pub fn ordered(instruction: &DrawInstruction) -> Option<Vec<Shape>> {
+    match instruction {
+        DrawInstruction::WorldSize(_) => None,
+        DrawInstruction::Frame(entities) => Some(entities.iter().map(Shape::from_entity).collect()),
+        DrawInstruction::Shapes(shapes) => {
+            let mut shapes = shapes.iter().collect::<Vec<&Layered>>();
+            // stable, the shapes of a layer keep their order
+            shapes.sort_by_key(|s| s.layer);
+            Some(shapes.into_iter().map(|s| s.shape.clone()).collect())
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Draws the frames in a terminal with ansi colors, to watch the problems without gtk.
+//! Every character shows two pixels with the upper half block : the foreground color is the upper
+//! pixel and the background color the lower one.
+use crate::graphics::raster::{frames_of, rasterize, Canvas};
+use crate::graphics::{DrawInstruction, ManyStepDrawable, Shape, SingleStepDrawable};
+use std::env;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Delay between two frames when playing, like the window's timer.
+pub const FRAME_DELAY: Duration = Duration::from_millis(200);
+
+/// Returns the number of columns and lines of the terminal, asked to the terminal itself, or from
+/// the COLUMNS and LINES variables when stdout is not one, or 80x24.
+pub fn terminal_size() -> (usize, usize) {
+    if let Some(size) = tty_size() {
+        return size;
+    }
+    let read = |name: &str, default: usize| {
+        env::var(name)
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|&v| v > 0)
+            .unwrap_or(default)
+    };
+    (read("COLUMNS", 80), read("LINES", 24))
+}
+
+/// The size of the terminal stdout writes to, if it is one.
+#[cfg(unix)]
+fn tty_size() -> Option<(usize, usize)> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    let res = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    match res == 0 && size.ws_col > 0 && size.ws_row > 0 {
+        true => Some((size.ws_col as usize, size.ws_row as usize)),
+        false => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn tty_size() -> Option<(usize, usize)> {
+    None
+}
+
+/// Draws the canvas, two rows of pixels per line.
+pub fn canvas_to_ansi(canvas: &Canvas) -> String {
+    let mut out = String::new();
+    for y in (0..canvas.height).step_by(2) {
+        for x in 0..canvas.width {
+            let up = canvas.get(x, y);
+            let down = match y + 1 < canvas.height {
+                true => canvas.get(x, y + 1),
+                false => [0, 0, 0],
+            };
+            out.push_str(&format!(
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                up[0], up[1], up[2], down[0], down[1], down[2]
+            ));
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// Draws the frame as big as the terminal allows, keeping the proportions of the world.
+/// One line is left for the prompt.
+pub fn render_frame(frame: &[Shape], world_size: [usize; 2]) -> String {
+    let (columns, lines) = terminal_size();
+    let (max_x, max_y) = (columns as f64, 2.0 * (lines.max(2) - 1) as f64);
+    let scale = (max_x / world_size[0] as f64).min(max_y / world_size[1] as f64);
+    let width = ((world_size[0] as f64 * scale) as usize).max(1);
+    let height = ((world_size[1] as f64 * scale) as usize).max(1);
+    canvas_to_ansi(&rasterize(frame, world_size, width, height))
+}
+
+/// Plays the frames one after the other, clearing the terminal between them.
+pub fn play(instructions: &[DrawInstruction], delay: Duration) {
+    let stdout = io::stdout();
+    for (world_size, frame) in frames_of(instructions) {
+        let mut out = stdout.lock();
+        let _ = write!(out, "\x1b[H\x1b[2J{}", render_frame(&frame, world_size));
+        let _ = out.flush();
+        thread::sleep(delay);
+    }
+}
+
+/// Watches the current state of the problem.
+pub fn watch_many<P: ManyStepDrawable>(problem: &P) {
+    play(&problem.get_frames(), FRAME_DELAY);
+}
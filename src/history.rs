@@ -0,0 +1,125 @@
+//! The history module
+//! Lets the network see more than the last observation of the problem : the observations and
+//! actions of the previous steps, and a memory it writes itself.
+//!
+//! The memory makes the network recurrent : its outputs after the action are given back as inputs
+//! at the next step of the same playout. Only the evolution scheme can learn it, the other ones
+//! have no target for the memory outputs.
+//!
+//! ```toml
+//! [learning]
+//! scheme = "evolution"
+//!
+//! [learning.history]
+//! observations = 2
+//! actions = 1
+//! memory = 4
+//! ```
+use lmsmw::network::Network;
+use rulinalg::vector::Vector;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Without anything the network only sees the current observation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HistorySettings {
+    /// Number of previous observations given with the current one.
+    pub observations: usize,
+    /// Number of previous actions given.
+    pub actions: usize,
+    /// Number of outputs given back as inputs at the next step, with the evolution scheme only.
+    pub memory: usize,
+}
+
+impl HistorySettings {
+    /// Number of inputs of the network, for problems of the given observation and action sizes.
+    pub fn input_size(&self, observation: usize, action: usize) -> usize {
+        observation * (1 + self.observations) + action * self.actions + self.memory
+    }
+
+    /// Number of outputs of the network : the action and the memory.
+    pub fn output_size(&self, action: usize) -> usize {
+        action + self.memory
+    }
+}
+
+/// What the network remembers of the playout it is playing, to create at the start of every
+/// playout.
+#[derive(Debug, Clone)]
+pub struct History {
+    settings: HistorySettings,
+    action_size: usize,
+    /// The last observations, the most recent first.
+    observations: VecDeque<Vector<f64>>,
+    /// The last actions, the most recent first.
+    actions: VecDeque<Vector<f64>>,
+    memory: Vector<f64>,
+}
+
+impl History {
+    /// Before the first step the observations are 0, the actions neutral and the memory 0.5.
+    pub fn new(settings: &HistorySettings, observation_size: usize, action_size: usize) -> Self {
+        History {
+            settings: settings.clone(),
+            action_size,
+            observations: (0..settings.observations)
+                .map(|_| Vector::zeros(observation_size))
+                .collect(),
+            actions: (0..settings.actions)
+                .map(|_| Vector::new(vec![0.5; action_size]))
+                .collect(),
+            memory: Vector::new(vec![0.5; settings.memory]),
+        }
+    }
+
+    /// The inputs of the network : the observation, the previous ones, the previous actions and
+    /// the memory.
+    pub fn inputs(&self, observation: &Vector<f64>) -> Vector<f64> {
+        let mut inputs = observation.iter().cloned().collect::<Vec<f64>>();
+        for previous in self.observations.iter().chain(self.actions.iter()) {
+            inputs.extend(previous.iter());
+        }
+        inputs.extend(self.memory.iter());
+        Vector::new(inputs)
+    }
+
+    /// The part of the outputs of the network that is the action.
+    pub fn action_of(&self, outputs: &Vector<f64>) -> Vector<f64> {
+        outputs.iter().take(self.action_size).cloned().collect()
+    }
+
+    /// The part of the outputs of the network that is the memory.
+    pub fn memory_of(&self, outputs: &Vector<f64>) -> Vector<f64> {
+        outputs.iter().skip(self.action_size).cloned().collect()
+    }
+
+    /// Remembers the step : the observation it was taken from, the action played and the outputs
+    /// of the network, for their memory.
+    pub fn record(
+        &mut self,
+        observation: Vector<f64>,
+        action: &Vector<f64>,
+        outputs: &Vector<f64>,
+    ) {
+        if self.settings.observations > 0 {
+            self.observations.pop_back();
+            self.observations.push_front(observation);
+        }
+        if self.settings.actions > 0 {
+            self.actions.pop_back();
+            self.actions.push_front(action.clone());
+        }
+        if self.settings.memory > 0 {
+            self.memory = self.memory_of(outputs);
+        }
+    }
+
+    /// Lets the network choose the action from the observation and remembers the step.
+    pub fn act(&mut self, net: &Network, observation: Vector<f64>) -> Vector<f64> {
+        let outputs = net.feed_forward(&self.inputs(&observation));
+        let action = self.action_of(&outputs);
+        self.record(observation, &action, &outputs);
+        action
+    }
+}
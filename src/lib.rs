@@ -2,13 +2,22 @@
 //! Trains neural network to supervise genetic algorithms, the neural network will get informations
 //! about how the learning is going and make a choice about how to modify parameters such as the
 //! mutation rate, elitism, childs per survivors etc...
+extern crate libc;
 extern crate ordered_float;
 extern crate rand;
 extern crate rulinalg;
 #[macro_use]
 extern crate lmsmw;
 pub mod algogen;
+pub mod config;
+pub mod exploration;
 pub mod graphics;
+pub mod history;
+pub mod netfile;
+pub mod observer;
 pub mod params;
 pub mod problems;
+pub mod random;
 pub mod reilearn;
+pub mod replay;
+pub mod trace;
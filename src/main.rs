@@ -4,86 +4,328 @@
 //! mutation rate, elitism, childs per survivors etc...
 extern crate cairo;
 extern crate gtk;
+extern crate libc;
 extern crate ordered_float;
 extern crate rand;
 extern crate rulinalg;
 #[macro_use]
 extern crate lmsmw;
 mod algogen;
+mod cli;
+mod config;
+mod exploration;
 pub mod graphics;
+mod history;
+mod netfile;
+mod observer;
 mod params;
 mod problems;
+mod random;
 mod reilearn;
+mod replay;
+mod trace;
 
-use self::graphics::app;
-use crate::algogen::{AlgoGen, ParamChoice};
-use crate::params::*;
-use crate::problems::turnaround::TurnAroundProblem;
+use self::graphics::{app, headless, terminal, ManyStepDrawable};
+use crate::algogen::{AlgoGen, AlgoGenConf};
+use crate::cli::{Command, Options};
+use crate::config::Config;
+use crate::history::{History, HistorySettings};
+use crate::netfile::NetworkMeta;
+use crate::observer::ObserverHandle;
+use crate::problems::registry::{ProblemMix, ProblemRegistry, RegisteredProblem};
 use crate::problems::GenericProblem;
 use crate::problems::ManyStepProblem;
-use crate::reilearn::{LearnParams, ReiLearn};
+use crate::problems::SingleStepProblem;
+use crate::random::GenRng;
+use crate::reilearn::{ReiLearn, TrainingReport, TrainingScheme};
 use lmsmw::network::Network;
 use rand::prelude::thread_rng;
-type Problem = TurnAroundProblem;
+use rand::Rng;
+use std::sync::Arc;
+type Problem = RegisteredProblem;
 
-/// Run the algorithm
+/// Run the command given on the command line.
 pub fn main() {
-    match &mut app::App::<Problem>::new() {
-        Ok(a) => a.start(PROB_CONF_SIZE),
-        Err(_) => panic!("gtk::init failed"),
+    let (command, options) = match cli::parse(std::env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let registry = Arc::new(ProblemRegistry::with_builtins());
+    let res = options.resolve_config().and_then(|config| {
+        let mix = ProblemMix::from_config(registry, &config)?;
+        run(command, mix, &config, &options)
+    });
+    if let Err(e) = res {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Runs the command on the problems.
+fn run(command: Command, mix: ProblemMix, config: &Config, options: &Options) -> Result<(), String> {
+    match command {
+        Command::Train => {
+            let (net, report) = gen_network(mix.clone(), config, options);
+            let meta = NetworkMeta {
+                problem: config.problem.name.clone(),
+                config: config.clone(),
+                best_score: Some(report.test_score),
+                validation_score: Some(report.best_validation),
+            };
+            netfile::save(&net, &meta, &options.out)?;
+            config.save_next_to(&options.out)?;
+            println!("network saved to {}", options.out);
+            if let Some(ref path) = options.render {
+                let mut a = AlgoGen::<Problem>::initiate_with(
+                    mix,
+                    config.algogen.clone(),
+                    &mut options.rng(),
+                );
+                play_with(&mut a, &net, &config.learning.history);
+                render(&a, config, path)?;
+            }
+            Ok(())
+        }
+        Command::Eval => {
+            let path = options
+                .net
+                .as_ref()
+                .ok_or_else(|| String::from("eval needs a network, use --net"))?;
+            let (net, meta) = netfile::load(path)?;
+            println!(
+                "network trained on {} with a test score of {:?} and a validation score of {:?}",
+                meta.problem, meta.best_score, meta.validation_score
+            );
+            let mut config = config.clone();
+            config.learning.history = meta.config.learning.history;
+            // the output is the console, the config goes with it
+            print!("{}", config.to_toml()?);
+            eval_network(net, mix, &config, options);
+            Ok(())
+        }
+        Command::Bench => {
+            print!("{}", config.to_toml()?);
+            bench_schemes(mix, config, options);
+            Ok(())
+        }
+        Command::Demo => {
+            let mut rng = options.rng();
+            let (net, history) = match options.net {
+                Some(ref path) => {
+                    let (net, meta) = netfile::load(path)?;
+                    (net, meta.config.learning.history)
+                }
+                None => (new_network(config, &mut rng), config.learning.history.clone()),
+            };
+            let a = demo_first(
+                AlgoGen::<Problem>::initiate_with(mix, config.algogen.clone(), &mut rng),
+                &net,
+                &history,
+            );
+            if let Some(ref path) = options.render {
+                render(&a, config, path)?;
+            }
+            let saved = match options.trace {
+                Some(ref path) if path.ends_with(".jsonl") => a.trace().save_jsonl(path),
+                Some(ref path) => a.trace().save_csv(path),
+                None => return Ok(()),
+            };
+            saved.map_err(|e| format!("could not save the trace : {}", e))?;
+            config.save_next_to(options.trace.as_ref().unwrap())
+        }
+        Command::Gui => {
+            if !mix.is_drawable() {
+                return Err(format!("the problem {} cannot be drawn", config.problem.name));
+            }
+            let registry = mix.registry().clone();
+            let names = registry
+                .drawable_names()
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<String>>();
+            app::App::<Problem>::new()?
+                .with_rng(options.rng())
+                .with_problems(&names, move |config: &Config| {
+                    let mix = ProblemMix::from_config(registry.clone(), config)?;
+                    match mix.is_drawable() {
+                        true => Ok(mix),
+                        false => Err(format!("the problem {} cannot be drawn", config.problem.name)),
+                    }
+                })
+                .start(mix, config.clone());
+            Ok(())
+        }
     }
 }
 
+/// Saves the frames of the first and last individuals of the run with the config next to them, or
+/// plays them in the terminal.
+fn render(a: &AlgoGen<Problem>, config: &Config, path: &str) -> Result<(), String> {
+    if path == "term" {
+        terminal::watch_many(a);
+        return Ok(());
+    }
+    let files = headless::save(
+        &a.get_frames(),
+        path,
+        config.window.width as usize,
+        config.window.height as usize,
+    )
+    .map_err(|e| format!("could not render the demo : {}", e))?;
+    println!("demo rendered to {} files", files.len());
+    config.save_next_to(path)
+}
+
+/// Creates a random network with the layers given in the config, drawing its weights from the
+/// generator so that a seeded run starts from the same network.
+fn new_network(config: &Config, rng: &mut GenRng) -> Network {
+    Network::new(config.network.layers(&config.learning.history), rng)
+}
+
+/// Creates the learner for the problems and settings given in the config.
+fn new_learner(
+    net: Network,
+    mix: ProblemMix,
+    config: &Config,
+    rng: &mut GenRng,
+) -> ReiLearn<AlgoGen<Problem>> {
+    let levels = config
+        .learning
+        .curriculum
+        .iter()
+        .map(|&fraction| AlgoGenConf {
+            problem: mix.scaled(fraction),
+            settings: config.algogen.clone(),
+        })
+        .collect();
+    let conf = AlgoGenConf {
+        problem: mix,
+        settings: config.algogen.clone(),
+    };
+    let mut rl = ReiLearn::with_rng(net, conf, config.learning.clone(), random::derive(rng));
+    rl.set_curriculum(levels);
+    rl
+}
+
 /// Creates a network, making it learn to supervise genetic algorithms and print its score on a
 /// set of examples.
-pub fn gen_network() {
-    let learn_params = LearnParams::new(
-        NB_EXAMPLE_PROBLEMS,
-        TESTS_PER_PROBLEM,
-        MAX_GEN,
-        STARTING_COEF,
-        COEF_MODIFICATOR,
-        PERCENT_ELITE,
+/// Returns the network that had the best score on the validation problems and the report of its
+/// training.
+pub fn gen_network(
+    mix: ProblemMix,
+    config: &Config,
+    options: &Options,
+) -> (Network, TrainingReport) {
+    let mut rng = options.rng();
+    let net = new_network(config, &mut rng);
+    let mut rl = new_learner(net, mix.clone(), config, &mut rng);
+    let mut demo =
+        || AlgoGen::<Problem>::initiate_with(mix.clone(), config.algogen.clone(), &mut rng);
+    rl.run_baseline();
+    rl.demonstrate_on(demo());
+    let report = rl.train(|rl, _, _| rl.demonstrate_on(demo()));
+    println!(
+        "kept the network of validation score {} after {} generations, test score : {}",
+        report.best_validation, report.generations, report.test_score
     );
-    let mut random = thread_rng();
-    let layers = layers![15, 40, 10, 6];
-    let net = Network::new(layers, &mut random);
-    let mut rl = ReiLearn::<AlgoGen<Problem>>::new(net, PROB_CONF_SIZE, learn_params);
-    normal_test(rl.get_test_problems().clone());
-    let mut random = thread_rng();
-    rl.demonstrate_on(AlgoGen::initiate(600, &mut random));
-    loop {
-        println!(
-            "score on test data with network : {}",
-            rl.run_on_test_example() / (1000_000.0 * TEST_DATA_SIZE as f64)
-        );
-        rl.next_gen();
-        rl.demonstrate_on(AlgoGen::initiate(1000, &mut random));
+    (rl.get_net().clone(), report)
+}
+
+/// Compares the network with genetic algorithms using fixed parameters and random decisions, on
+/// the same test problems and normalized the same way.
+pub fn eval_network(net: Network, mix: ProblemMix, config: &Config, options: &Options) {
+    let mut rng = options.rng();
+    let rl = new_learner(net, mix, config, &mut rng);
+    let with_net = rl.run_on_test_example() / rl.get_test_problems().len() as f64;
+    let neutral = rl.neutral_outputs();
+    let fixed = rl.score_on_test(|_| neutral.clone());
+    let size = rl.output_size();
+    let random_choices = rl.score_on_test(|_| (0..size).map(|_| rng.gen::<f64>()).collect());
+    println!("network : {}", with_net);
+    println!("fixed parameters : {}", fixed);
+    println!("random decisions : {}", random_choices);
+}
+
+/// Trains the same network with every training scheme on the same problems, prints their
+/// validation scores after every generation and the test score of the network each one kept.
+pub fn bench_schemes(mix: ProblemMix, config: &Config, options: &Options) {
+    let schemes = [
+        TrainingScheme::EliteImitation,
+        TrainingScheme::Reinforce,
+        TrainingScheme::Evolution,
+        TrainingScheme::Branching,
+    ];
+    let seed = options.seed.unwrap_or_else(|| thread_rng().gen());
+    println!("benchmark with seed {}", seed);
+    let net = new_network(config, &mut random::seeded(seed));
+    let mut scores = vec![];
+    for scheme in schemes.iter() {
+        let mut config = config.clone();
+        config.learning.scheme = *scheme;
+        let mut rl = new_learner(net.clone(), mix.clone(), &config, &mut random::seeded(seed));
+        let mut curve = vec![rl.run_on_validation()];
+        let report = rl.train(|_, _, score| curve.push(score));
+        scores.push((curve, report.test_score));
     }
+    let names = schemes.iter().map(|s| format!("{:?}", s)).collect::<Vec<String>>();
+    println!("generation\t{}", names.join("\t"));
+    let longest = scores.iter().map(|s| s.0.len()).max().unwrap_or(0);
+    for generation in 0..longest {
+        // the trainings stopped early have no score for the last generations
+        let row = scores
+            .iter()
+            .map(|(curve, _)| curve.get(generation).map(|s| s.to_string()).unwrap_or_default())
+            .collect::<Vec<String>>();
+        println!("{}\t{}", generation, row.join("\t"));
+    }
+    let tests = scores
+        .iter()
+        .map(|(_, test)| test.to_string())
+        .collect::<Vec<String>>();
+    println!("test\t{}", tests.join("\t"));
 }
 
-/// Demonstrate how the network performs on a given problem.
-pub fn demo_first(mut a: AlgoGen<Problem>, net: &Network) {
-    println!("demo for first ");
-    for _ in 0..10 {
-        a.print_state();
-        let inputs = a.get_state();
-        a.make_step(&net.feed_forward(&inputs));
+/// Lets the network choose the parameters until the end of the run.
+pub fn play_with<P: SingleStepProblem + Clone>(
+    a: &mut AlgoGen<P>,
+    net: &Network,
+    history: &HistorySettings,
+) where
+    P::Sol: Clone,
+{
+    let mut history = History::new(history, a.output_space(), a.input_space());
+    for _ in 0..a.max_step().unwrap_or(params::MAX_GENETIC_ALG_GEN) {
+        let action = history.act(net, a.get_state());
+        a.make_step(&action);
+        if a.is_solved() {
+            break;
+        }
     }
-    println!("for a total score of {}", a.evaluate());
 }
 
-/// Run a genetic algorithm with hand crafted parameters against the test problems.
-pub fn normal_test(mut algs: Vec<AlgoGen<Problem>>) {
-    let mut score = 0.0;
-    for a in algs.iter_mut() {
-        for _ in 0..10 {
-            a.next_gen(ParamChoice::same());
+/// Demonstrate how the network performs on a given problem.
+pub fn demo_first<P: SingleStepProblem + Clone>(
+    mut a: AlgoGen<P>,
+    net: &Network,
+    history: &HistorySettings,
+) -> AlgoGen<P>
+where
+    P::Sol: Clone,
+{
+    println!("demo for first ");
+    a.set_observer(ObserverHandle::console());
+    let mut history = History::new(history, a.output_space(), a.input_space());
+    for _ in 0..a.max_step().unwrap_or(params::MAX_GENETIC_ALG_GEN) {
+        a.print_state();
+        let action = history.act(net, a.get_state());
+        a.make_step(&action);
+        if a.is_solved() {
+            break;
         }
-        score += a.evaluate();
     }
-    println!(
-        "total score on test data without network is : {}",
-        score / (1000_000.0 * TEST_DATA_SIZE as f64)
-    );
+    println!("for a total score of {}", a.evaluate());
+    a
 }
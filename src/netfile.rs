@@ -0,0 +1,155 @@
+//! The netfile module
+//! Saves the supervisor networks to disk and loads them back.
+//!
+//! The file is a toml document holding the weights and what is needed to use them again :
+//! the layout of the observations and actions the network was trained with, what it sees of the
+//! previous steps, the problem, the config of the training and the best score obtained on the
+//! test problems.
+use crate::algogen::{GEN_RESULT_SIZE, PARAM_CHOICE_SIZE, SCHEMA_VERSION};
+use crate::config::Config;
+use crate::history::HistorySettings;
+use crate::random;
+use lmsmw::network::Network;
+use rulinalg::matrix::{BaseMatrix, Matrix};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Returns all the weights of the network, layer after layer.
+pub fn flatten(net: &Network) -> Vec<f64> {
+    net.get_weights()
+        .iter()
+        .flat_map(|m| m.data().clone())
+        .collect()
+}
+
+/// Creates a network shaped like the template with the given weights.
+pub fn unflatten(template: &Network, weights: &[f64]) -> Result<Network, String> {
+    let mut start = 0;
+    let mut matrices = vec![];
+    for m in template.get_weights().iter() {
+        let len = m.rows() * m.cols();
+        if start + len > weights.len() {
+            return Err(format!(
+                "not enough weights : expected more than {}, got {}",
+                start + len,
+                weights.len()
+            ));
+        }
+        matrices.push(Matrix::new(
+            m.rows(),
+            m.cols(),
+            weights[start..start + len].to_vec(),
+        ));
+        start += len;
+    }
+    if start != weights.len() {
+        return Err(format!(
+            "too many weights : expected {}, got {}",
+            start,
+            weights.len()
+        ));
+    }
+    let mut net = template.clone();
+    net.set_weights(matrices);
+    Ok(net)
+}
+
+/// Creates a network with the given layers and weights.
+pub fn from_weights(layers: Vec<usize>, weights: &[f64]) -> Result<Network, String> {
+    // every weight is replaced, a fixed seed keeps the loading deterministic
+    let template = Network::new(layers, &mut random::seeded(0));
+    unflatten(&template, weights)
+}
+
+/// What is known about a network besides its weights.
+#[derive(Debug, Clone)]
+pub struct NetworkMeta {
+    /// The problems it was trained on, as given in the config.
+    pub problem: String,
+    pub config: Config,
+    /// The average score on the test problems, held out of the training.
+    pub best_score: Option<f64>,
+    /// The average score on the validation problems it was chosen on.
+    pub validation_score: Option<f64>,
+}
+
+/// The content of the file, the values come before the config's tables.
+#[derive(Serialize, Deserialize)]
+struct NetworkFile {
+    schema_version: u32,
+    /// Size of an observation, the network reads more inputs with a history.
+    inputs: usize,
+    /// Size of an action, the network gives more outputs with a memory.
+    outputs: usize,
+    problem: String,
+    best_score: Option<f64>,
+    validation_score: Option<f64>,
+    layers: Vec<usize>,
+    weights: Vec<f64>,
+    /// Missing from the files saved before the history, they read the observation alone.
+    #[serde(default)]
+    history: HistorySettings,
+    config: Config,
+}
+
+pub fn save<T: AsRef<Path>>(net: &Network, meta: &NetworkMeta, path: T) -> Result<(), String> {
+    let file = NetworkFile {
+        schema_version: SCHEMA_VERSION,
+        inputs: GEN_RESULT_SIZE,
+        outputs: PARAM_CHOICE_SIZE,
+        problem: meta.problem.clone(),
+        best_score: meta.best_score,
+        validation_score: meta.validation_score,
+        layers: net.layers().to_vec(),
+        weights: flatten(net),
+        history: meta.config.learning.history.clone(),
+        config: meta.config.clone(),
+    };
+    let content = toml::to_string(&file).map_err(|e| e.to_string())?;
+    fs::write(&path, content)
+        .map_err(|e| format!("could not write {} : {}", path.as_ref().display(), e))
+}
+
+/// Loads the network, refusing it if it does not read the observations or give the actions the
+/// way AlgoGen currently does.
+pub fn load<T: AsRef<Path>>(path: T) -> Result<(Network, NetworkMeta), String> {
+    let name = path.as_ref().display().to_string();
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("could not read {} : {}", name, e))?;
+    let file: NetworkFile = toml::from_str(&content)
+        .map_err(|e| format!("{} is not a network file : {}", name, e))?;
+    if file.schema_version != SCHEMA_VERSION {
+        return Err(format!(
+            "{} was saved with the observation/action schema {}, the current one is {}",
+            name, file.schema_version, SCHEMA_VERSION
+        ));
+    }
+    if file.inputs != GEN_RESULT_SIZE || file.outputs != PARAM_CHOICE_SIZE {
+        return Err(format!(
+            "{} reads {} observations and gives {} actions, expected {} and {}",
+            name, file.inputs, file.outputs, GEN_RESULT_SIZE, PARAM_CHOICE_SIZE
+        ));
+    }
+    let inputs = file.history.input_size(file.inputs, file.outputs);
+    let outputs = file.history.output_size(file.outputs);
+    if file.layers.first() != Some(&inputs) || file.layers.last() != Some(&outputs) {
+        return Err(format!(
+            "{} has layers {:?} that do not match its {} inputs and {} outputs, history {:?}",
+            name, file.layers, inputs, outputs, file.history
+        ));
+    }
+    let net = from_weights(file.layers, &file.weights).map_err(|e| format!("{} : {}", name, e))?;
+    let mut config = file.config;
+    // the network can only be played with the history it was trained with
+    config.learning.history = file.history;
+    Ok((
+        net,
+        NetworkMeta {
+            problem: file.problem,
+            config,
+            best_score: file.best_score,
+            validation_score: file.validation_score,
+        },
+    ))
+}
@@ -0,0 +1,221 @@
+//! The observer module
+//! AlgoGen and ReiLearn report their progress as events sent to an observer, the library user
+//! chooses what to do with them : print them, log them to a file or ignore them.
+use crate::algogen::{GenResult, ParamChoice};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Something that happened while learning.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// A genetic algorithm finished a generation.
+    GenerationFinished {
+        generation: usize,
+        stats: GenResult,
+        pop_size: usize,
+        evaluations: usize,
+    },
+    /// The parameters chosen were not sane and were modified before being applied.
+    ParamsClamped {
+        requested: ParamChoice,
+        applied: ParamChoice,
+    },
+    /// The elite was bigger than the population after the kills.
+    EliteTooBig { params: ParamChoice, pop_size: usize },
+    /// The supervisor played a whole problem.
+    PlayoutFinished { score: f64, steps: usize },
+    /// The supervisor network was trained on new examples, explored with the coef.
+    /// With the evolution the examples are the networks tried and the coef their deviation.
+    NetworkRetrained { examples: usize, coef: f64 },
+    /// The supervisor was evaluated on the test problems, the score is the average per problem.
+    TestScoreComputed { score: f64 },
+    /// The supervisor was evaluated on the validation problems, the score is the average per
+    /// problem.
+    ValidationScoreComputed { score: f64 },
+    /// The validation problems were played with neutral outputs, the score is the average per
+    /// problem.
+    BaselineComputed { score: f64 },
+    /// The training problems are now at this difficulty level, counted from 0.
+    LevelChanged { level: usize, levels: usize },
+    /// A training ended, keeping the network with the best validation score.
+    TrainingStopped {
+        generations: usize,
+        best_validation: f64,
+    },
+    /// Frames were recorded for a demonstration.
+    DemoRecorded { frames: usize },
+    /// A genetic algorithm was asked for its state, during a demonstration.
+    StateReported { stats: GenResult, pop_size: usize },
+    /// A demonstration ended with the score.
+    DemoPlayed { score: f64 },
+}
+
+impl Event {
+    /// Events that are sent very often (every generation or every playout).
+    pub fn is_frequent(&self) -> bool {
+        match self {
+            Event::GenerationFinished { .. }
+            | Event::ParamsClamped { .. }
+            | Event::PlayoutFinished { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::GenerationFinished {
+                generation,
+                stats,
+                pop_size,
+                evaluations,
+            } => write!(
+                f,
+                "generation {} : best : {}\tmin : {}\t pop : {}\t evaluations : {}",
+                generation, stats.max, stats.min, pop_size, evaluations
+            ),
+            Event::ParamsClamped { requested, applied } => {
+                write!(f, "params clamped : {:?} became {:?}", requested, applied)
+            }
+            Event::EliteTooBig { params, pop_size } => {
+                write!(f, "echec : elite too big for pop {} : {:?}", pop_size, params)
+            }
+            Event::PlayoutFinished { score, steps } => {
+                write!(f, "playout finished in {} steps with score {}", steps, score)
+            }
+            Event::NetworkRetrained { examples, coef } => write!(
+                f,
+                "network trained on {} examples with exploration coef {}",
+                examples, coef
+            ),
+            Event::TestScoreComputed { score } => {
+                write!(f, "score on test data with network : {}", score)
+            }
+            Event::ValidationScoreComputed { score } => {
+                write!(f, "score on validation data with network : {}", score)
+            }
+            Event::BaselineComputed { score } => {
+                write!(f, "score on validation data without network : {}", score)
+            }
+            Event::LevelChanged { level, levels } => {
+                write!(f, "difficulty level {} of {}", level + 1, levels)
+            }
+            Event::TrainingStopped {
+                generations,
+                best_validation,
+            } => write!(
+                f,
+                "training stopped after {} generations, best validation score : {}",
+                generations, best_validation
+            ),
+            Event::DemoRecorded { frames } => write!(f, "demo recorded : {} frames", frames),
+            Event::StateReported { stats, pop_size } => write!(
+                f,
+                "best : {}\tmin : {}\t pop : {}",
+                stats.max, stats.min, pop_size
+            ),
+            Event::DemoPlayed { score } => write!(f, "demo finished with score {}", score),
+        }
+    }
+}
+
+/// Receives the events.
+pub trait Observer: Send {
+    fn notify(&mut self, event: &Event);
+}
+
+/// Prints the events on stdout, the frequent ones only if verbose.
+pub struct ConsoleObserver {
+    verbose: bool,
+}
+
+impl ConsoleObserver {
+    pub fn new() -> Self {
+        ConsoleObserver { verbose: false }
+    }
+
+    pub fn verbose() -> Self {
+        ConsoleObserver { verbose: true }
+    }
+}
+
+impl Observer for ConsoleObserver {
+    fn notify(&mut self, event: &Event) {
+        if self.verbose || !event.is_frequent() {
+            println!("{}", event);
+        }
+    }
+}
+
+/// Writes every event on its own line in a file.
+pub struct FileObserver {
+    out: BufWriter<File>,
+}
+
+impl FileObserver {
+    pub fn create<T: AsRef<Path>>(path: T) -> io::Result<Self> {
+        Ok(FileObserver {
+            out: BufWriter::new(File::create(path)?),
+        })
+    }
+}
+
+impl Observer for FileObserver {
+    fn notify(&mut self, event: &Event) {
+        // a failing log must not stop the learning.
+        let _ = writeln!(self.out, "{}", event);
+        if !event.is_frequent() {
+            let _ = self.out.flush();
+        }
+    }
+}
+
+/// Ignores everything.
+pub struct SilentObserver;
+
+impl Observer for SilentObserver {
+    fn notify(&mut self, _event: &Event) {}
+}
+
+/// Sends the events to several observers.
+pub struct ObserverList(pub Vec<Box<dyn Observer>>);
+
+impl Observer for ObserverList {
+    fn notify(&mut self, event: &Event) {
+        for observer in self.0.iter_mut() {
+            observer.notify(event);
+        }
+    }
+}
+
+/// Shared handle to an observer so that it can be given to cloned problems and threads.
+#[derive(Clone)]
+pub struct ObserverHandle(Arc<Mutex<dyn Observer>>);
+
+impl ObserverHandle {
+    pub fn new<O: Observer + 'static>(observer: O) -> Self {
+        ObserverHandle(Arc::new(Mutex::new(observer)))
+    }
+
+    pub fn console() -> Self {
+        Self::new(ConsoleObserver::new())
+    }
+
+    pub fn silent() -> Self {
+        Self::new(SilentObserver)
+    }
+
+    pub fn notify(&self, event: Event) {
+        self.0.lock().unwrap().notify(&event);
+    }
+}
+
+impl fmt::Debug for ObserverHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ObserverHandle")
+    }
+}
@@ -10,3 +10,31 @@ pub const PROB_CONF_SIZE: usize = 50;
 pub const MAX_GENETIC_ALG_GEN: usize = 50;
 pub const WIDTH: i32 = 400;
 pub const HEIGHT: i32 = 400;
+pub const CHART_WIDTH: i32 = 300;
+pub const GENERATIONS: usize = 10;
+pub const GRAD_ITERS: usize = 20;
+pub const GRAD_STEP: f64 = 0.01;
+pub const GRAD_BATCH_SIZE: usize = 200;
+pub const LVBM_BATCH_SIZE: usize = 50;
+pub const AIM_SCORE: f64 = 0.05;
+pub const HIDDEN_LAYERS: [usize; 2] = [40, 10];
+pub const POLICY_STEP: f64 = 0.5;
+pub const BASELINE_RATE: f64 = 0.05;
+pub const ES_POPULATION: usize = 10;
+pub const ES_PROBLEMS: usize = 10;
+pub const ES_SIGMA: f64 = 0.05;
+pub const ES_STEP: f64 = 0.02;
+pub const OU_THETA: f64 = 0.15;
+pub const COEF_STEP: f64 = 0.1;
+pub const GAMMA: f64 = 0.9;
+pub const BRANCH_FACTOR: usize = 4;
+pub const BRANCH_HORIZON: usize = 5;
+pub const VALIDATION_DATA_SIZE: usize = 200;
+pub const PATIENCE: usize = 5;
+pub const RANK_BASELINES: usize = 9;
+pub const MIN_BASELINE_SCORE: f64 = 1e-9;
+pub const BASELINE_FLOOR: f64 = 0.1;
+pub const MAX_RATIO: f64 = 10.0;
+pub const REPLAY_CAPACITY: usize = 10000;
+pub const REPLAY_RATIO: f64 = 0.25;
+pub const REPLAY_PRIORITY: f64 = 1.0;
@@ -1,5 +1,6 @@
 use crate::problems::{GenericProblem, ManyStepProblem};
-use rand::{prelude::ThreadRng, Rng};
+use crate::random::GenRng;
+use rand::Rng;
 use rulinalg::vector::Vector;
 
 /// One simple problem step by step.
@@ -15,7 +16,7 @@ pub struct EasyStep {
 impl GenericProblem for EasyStep {
     type ProblemConfig = usize;
 
-    fn random(xsr: &mut ThreadRng, conf: &usize) -> Self {
+    fn random(xsr: &mut GenRng, conf: &usize) -> Self {
         EasyStep {
             visited: (0..*conf).map(|_| 1.0).collect(),
             my_pos: xsr.gen::<usize>() % *conf,
@@ -9,7 +9,8 @@
 use crate::graphics::DrawInstruction;
 use crate::graphics::SingleStepDrawable;
 use crate::problems::{GenericProblem, GenericSol, SingleStepProblem, Solution};
-use rand::{distributions::Standard, prelude::ThreadRng, Rng};
+use crate::random::GenRng;
+use rand::{distributions::Standard, Rng};
 use rulinalg::vector::Vector;
 
 pub const ARENA_SIZE: f64 = 100.0;
@@ -81,7 +82,7 @@ impl TurnAroundProblem {
 impl GenericProblem for TurnAroundProblem {
     type ProblemConfig = usize;
 
-    fn random(xsr: &mut ThreadRng, _conf: &usize) -> Self {
+    fn random(xsr: &mut GenRng, _conf: &usize) -> Self {
         TurnAroundProblem {
             initial_pos: xsr
                 .sample_iter(&Standard)
@@ -3,7 +3,8 @@
 //! Solve Ax=y
 //! Level : Easy
 use crate::problems::{GenericProblem, GenericSol, SingleStepProblem, Solution};
-use rand::{distributions::Standard, prelude::ThreadRng, Rng};
+use crate::random::GenRng;
+use rand::{distributions::Standard, Rng};
 use rulinalg::{
     matrix::{BaseMatrix, Matrix},
     vector::Vector,
@@ -28,7 +29,7 @@ impl LinearEquationProblem {
 impl GenericProblem for LinearEquationProblem {
     type ProblemConfig = usize;
 
-    fn random(xsr: &mut ThreadRng, _conf: &usize) -> Self {
+    fn random(xsr: &mut GenRng, _conf: &usize) -> Self {
         LinearEquationProblem {
             matrix_a: Matrix::new(
                 *_conf,
@@ -1,10 +1,11 @@
 //! Just a problem where we need to find our way in a maze.
 //!
 //! Level : Easy
-use crate::graphics::DrawInstruction;
 use crate::graphics::SingleStepDrawable;
+use crate::graphics::{rgb, rgba, DrawInstruction, Point, Shape};
 use crate::problems::{GenericProblem, GenericSol, SingleStepProblem, Solution};
-use rand::{prelude::ThreadRng, Rng};
+use crate::random::GenRng;
+use rand::Rng;
 use rulinalg::vector::Vector;
 
 const SOL_SIZE: f64 = 10.0;
@@ -49,7 +50,7 @@ impl MazeProblem {
     /// When exploring an area, checks that it has only a maximum of 1 neibourgh explored
     /// A possible move must not
     /// Returns the end of the maze.
-    fn create_maze(maze: &mut Vec<Vec<bool>>, xsr: &mut ThreadRng) -> Vec<usize> {
+    fn create_maze(maze: &mut Vec<Vec<bool>>, xsr: &mut GenRng) -> Vec<usize> {
         let current_pos = vec![0usize, 0usize];
         let mut queue = vec![current_pos.clone()];
         let mut farthest = (0, vec![0, 0]);
@@ -131,10 +132,14 @@ impl MazeProblem {
     ) -> f64 {
         let mut pos = vec![0, 0];
         let mut mv = vec![0.0, 0.0];
+        let mut path = vec![];
         for i in 0..(self.maze_size as f64 * SOL_SIZE) as usize {
             match opt {
                 RunOption::Verbose => self.print_pos(&pos),
-                RunOption::Draw => frames.push(self.get_frame(&pos)),
+                RunOption::Draw => {
+                    path.push([pos[0] as f64 + 0.5, pos[1] as f64 + 0.5]);
+                    frames.push(self.get_frame(&pos, &path));
+                }
                 _ => (),
             }
             mv[0] = Self::clamp(sol.1[i]);
@@ -184,27 +189,55 @@ impl MazeProblem {
                     print!("##");
                 }
             }
-            println!("");
+            println!();
         }
         println!("----\n");
     }
 
-    fn get_frame(&self, pos: &Vec<i32>) -> DrawInstruction {
+    /// Draws the walls, the exit in red, the path followed so far and the player in green.
+    fn get_frame(&self, pos: &Vec<i32>, path: &[Point]) -> DrawInstruction {
         let mut ret = Vec::new();
         for x in 0..self.maze_size {
             for y in 0..self.maze_size {
-                if pos[0] as usize == x && pos[1] as usize == y {
-                    ret.push([x as f64, y as f64, 1.0, 1.0, 0.0, 1.0, 0.0])
-                } else if self.end[0] as usize == x && self.end[1] as usize == y {
-                    ret.push([x as f64, y as f64, 1.0, 1.0, 1.0, 0.0, 0.0])
-                } else if self.maze[x][y] {
-                    ret.push([x as f64, y as f64, 1.0, 1.0, 1.0, 1.0, 1.0])
-                } else {
-                    ret.push([x as f64, y as f64, 1.0, 1.0, 0.0, 0.0, 0.0])
-                }
+                let color = match self.maze[x][y] {
+                    true => rgb(1.0, 1.0, 1.0),
+                    false => rgb(0.0, 0.0, 0.0),
+                };
+                ret.push(
+                    Shape::Rect {
+                        pos: [x as f64, y as f64],
+                        size: [1.0, 1.0],
+                        color,
+                    }
+                    .on(0),
+                );
             }
         }
-        DrawInstruction::Frame(ret)
+        ret.push(
+            Shape::Rect {
+                pos: [self.end[0], self.end[1]],
+                size: [1.0, 1.0],
+                color: rgb(1.0, 0.0, 0.0),
+            }
+            .on(1),
+        );
+        ret.push(
+            Shape::Line {
+                points: path.to_vec(),
+                width: 0.3,
+                color: rgba(0.0, 0.6, 0.0, 0.5),
+            }
+            .on(2),
+        );
+        ret.push(
+            Shape::Circle {
+                center: [pos[0] as f64 + 0.5, pos[1] as f64 + 0.5],
+                radius: 0.4,
+                color: rgb(0.0, 1.0, 0.0),
+            }
+            .on(3),
+        );
+        DrawInstruction::Shapes(ret)
     }
 }
 
@@ -220,7 +253,7 @@ impl SingleStepDrawable for MazeProblem {
 impl GenericProblem for MazeProblem {
     type ProblemConfig = usize;
 
-    fn random(xsr: &mut ThreadRng, conf: &usize) -> Self {
+    fn random(xsr: &mut GenRng, conf: &usize) -> Self {
         let mut maze: Vec<Vec<bool>> = (0..*conf)
             .map(|_| (0..*conf).map(|_| false).collect())
             .collect();
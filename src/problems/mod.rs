@@ -5,16 +5,19 @@ pub mod easystep;
 pub mod follow;
 pub mod lineareq;
 pub mod maze;
+pub mod registry;
 pub mod turnaround;
 pub mod walljump;
-use rand::{distributions::Distribution, distributions::Standard, prelude::ThreadRng, Rng};
+use crate::observer::ObserverHandle;
+use crate::random::GenRng;
+use rand::{distributions::Distribution, distributions::Standard, Rng};
 use rulinalg::vector::Vector;
 use std::fmt::Debug;
 use std::marker::Sized;
 
 pub trait Solution: Debug + Sized {
     type SolConfig;
-    fn random(xsr: &mut ThreadRng, sol_conf: &Self::SolConfig) -> Self;
+    fn random(xsr: &mut GenRng, sol_conf: &Self::SolConfig) -> Self;
     fn add_score(&mut self, score: f64);
     fn reset_score(&mut self);
     fn get_score(&self) -> f64;
@@ -22,7 +25,7 @@ pub trait Solution: Debug + Sized {
     fn as_mut_vec(&mut self) -> &mut Vector<f64>;
     fn as_vec(&self) -> &Vector<f64>;
 
-    fn child(&self, other: &Self, xsr: &mut ThreadRng) -> Self {
+    fn child(&self, other: &Self, xsr: &mut GenRng) -> Self {
         Self::from_vec(
             self.as_vec()
                 .iter()
@@ -35,7 +38,7 @@ pub trait Solution: Debug + Sized {
         )
     }
 
-    fn mutate(&mut self, mutrate: f64, xsr: &mut ThreadRng) {
+    fn mutate(&mut self, mutrate: f64, xsr: &mut GenRng) {
         for s in self.as_mut_vec().iter_mut() {
             *s *= 2.0 * mutrate * (xsr.gen::<f64>() - 0.5);
         }
@@ -46,7 +49,7 @@ pub trait GenericProblem : Sized{
     /// The problem's configuration.
     type ProblemConfig: Clone + Send;
     /// Creates a random problem.
-    fn random(xsr: &mut ThreadRng, prob_conf: &Self::ProblemConfig) -> Self
+    fn random(xsr: &mut GenRng, prob_conf: &Self::ProblemConfig) -> Self
         where
             Self: Sized;
     /// Prints the state in readable format.
@@ -88,15 +91,26 @@ pub trait ManyStepProblem: Debug + Sized + GenericProblem + Clone {
     fn output_space(&self) -> usize;
     /// Tells if the problem arrived to an end.
     fn is_solved(&self) -> bool;
+    /// The reward of the last step, for the problems that know which step did what.
+    /// Without it every step of a playout is credited with the final evaluation.
+    fn step_reward(&self) -> Option<f64> {
+        None
+    }
+    /// Sends the events of the problem to the observer, for the problems that have some.
+    fn set_observer(&mut self, _observer: ObserverHandle) {}
+    /// Replaces the generator of the problem, for the problems drawing random numbers while they
+    /// are played. A clone keeps drawing the same numbers as the problem it came from.
+    fn reseed(&mut self, _rng: GenRng) {}
 }
 
-type GenericSol = (f64, Vector<f64>);
+/// A solution that is a simple vector of values.
+pub type GenericSol = (f64, Vector<f64>);
 
 impl Solution for GenericSol {
     /// the length of the vector
     type SolConfig = usize;
 
-    fn random(xsr: &mut ThreadRng, sol_conf: &<Self as Solution>::SolConfig) -> Self {
+    fn random(xsr: &mut GenRng, sol_conf: &<Self as Solution>::SolConfig) -> Self {
         (
             0.0,
             Vector::from(
@@ -152,7 +166,7 @@ pub trait SupervisableSolver: SingleStepProblemSolver + Debug + Clone + Sized{
     fn best_sol(&self) -> <<Self as SingleStepProblemSolver>::Problem as SingleStepProblem>::Sol;
     fn get_state(&self) -> Vector<f64>;
     fn evaluate(&self) -> f64;
-    fn random(xsr: &mut ThreadRng, prob_conf: &Self::CreateParam) -> Self
+    fn random(xsr: &mut GenRng, prob_conf: &Self::CreateParam) -> Self
     where
     Self: Sized;
     fn input_space(&self) -> usize;
@@ -169,7 +183,7 @@ where
 {
     type ProblemConfig = Create;
 
-    fn random(xsr: &mut ThreadRng, prob_conf: &<Self as GenericProblem>::ProblemConfig) -> Self {
+    fn random(xsr: &mut GenRng, prob_conf: &<Self as GenericProblem>::ProblemConfig) -> Self {
         <Self as SupervisableSolver>::random(xsr, prob_conf)
     }
 
@@ -247,7 +261,7 @@ impl<A, G> GenericProblem for PlayAgainst<A, G>
 {
     type ProblemConfig = G::ProblemConfig;
 
-    fn random(xsr: &mut ThreadRng, prob_conf: & <Self as GenericProblem>::ProblemConfig) -> Self where
+    fn random(xsr: &mut GenRng, prob_conf: & <Self as GenericProblem>::ProblemConfig) -> Self where
         Self: Sized {
         let game = G::random(xsr, prob_conf);
         PlayAgainst {
@@ -306,25 +320,31 @@ impl<P : ManyStepProblem<ProblemConfig=usize>> SingleStepProblem for P {
         self.input_space() * self.max_step().expect("cannot create a single step solution without knowing the number of steps")
     }
 
+    /// Plays the moves of the solution one after the other on a copy of the problem.
     fn evaluate(&mut self, sol: &mut <Self as SingleStepProblem>::Sol) -> f64 {
+        let mut this = self.clone();
         let solution = sol.1.data();
-        let mut i = 0;
-        let step_size = self.input_space();
-        while !self.is_solved(){
-            let mv = &solution[step_size*i..(step_size+1)*i];
-            self.make_step(&Vector::new(mv));
+        let step_size = this.input_space();
+        for i in 0..this.max_step().unwrap_or(0) {
+            if this.is_solved() {
+                break;
+            }
+            let mv = &solution[step_size * i..step_size * (i + 1)];
+            this.make_step(&Vector::new(mv));
         }
-        <Self as ManyStepProblem>::evaluate(self)
+        <Self as ManyStepProblem>::evaluate(&this)
     }
 
     fn demonstrate(&self, sol: &<Self as SingleStepProblem>::Sol) {
         let mut this = self.clone();
         let solution = sol.1.data();
-        let mut i = 0;
         let step_size = this.input_space();
-        while !this.is_solved(){
+        for i in 0..this.max_step().unwrap_or(0) {
+            if this.is_solved() {
+                break;
+            }
             this.print_state();
-            let mv = &solution[step_size*i..(step_size+1)*i];
+            let mv = &solution[step_size * i..step_size * (i + 1)];
             this.make_step(&Vector::new(mv));
         }
         this.print_state();
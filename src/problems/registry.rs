@@ -0,0 +1,415 @@
+//! Registry of the problems, to choose them by name at runtime.
+//!
+//! Every problem is registered with a constructor and a parser for its config, the problems
+//! created this way all have the same type : RegisteredProblem, so the learning can be done on
+//! any problem or mixture of problems without recompiling.
+//! Other crates can register their own problems as long as their solutions are GenericSol.
+use super::{
+    easycompilation::AllProblemsCompilation, easyproblem::EasyProblem, easystep::EasyStep,
+    lineareq::LinearEquationProblem, maze::MazeProblem, turnaround::TurnAroundProblem,
+    walljump::WallJumpProblem,
+};
+use crate::config::Config;
+use crate::graphics::{DrawInstruction, SingleStepDrawable};
+use crate::problems::{GenericProblem, GenericSol, SingleStepProblem, Solution};
+use crate::random::GenRng;
+use rand::Rng;
+use std::any::Any;
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+
+/// A problem whose type is hidden, see RegisteredProblem.
+pub trait DynProblem: Debug {
+    fn get_sol_conf(&self) -> usize;
+    fn evaluate(&mut self, sol: &mut GenericSol) -> f64;
+    fn demonstrate(&self, sol: &GenericSol);
+    fn print_state(&self);
+    /// The frames of the solution, empty if the problem can not be drawn.
+    fn get_frames(&self, sol: &GenericSol) -> Vec<DrawInstruction>;
+    fn box_clone(&self) -> Box<dyn DynProblem>;
+}
+
+/// Wraps a problem that can not be drawn.
+#[derive(Debug, Clone)]
+struct Plain<P>(P);
+
+impl<P> DynProblem for Plain<P>
+where
+    P: SingleStepProblem<Sol = GenericSol> + Clone + 'static,
+{
+    fn get_sol_conf(&self) -> usize {
+        self.0.get_sol_conf()
+    }
+
+    fn evaluate(&mut self, sol: &mut GenericSol) -> f64 {
+        self.0.evaluate(sol)
+    }
+
+    fn demonstrate(&self, sol: &GenericSol) {
+        self.0.demonstrate(sol)
+    }
+
+    fn print_state(&self) {
+        self.0.print_state()
+    }
+
+    fn get_frames(&self, _sol: &GenericSol) -> Vec<DrawInstruction> {
+        vec![]
+    }
+
+    fn box_clone(&self) -> Box<dyn DynProblem> {
+        Box::new(self.clone())
+    }
+}
+
+/// Wraps a problem that can be drawn.
+#[derive(Debug, Clone)]
+struct Drawn<P>(P);
+
+impl<P> DynProblem for Drawn<P>
+where
+    P: SingleStepDrawable<Sol = GenericSol> + Clone + 'static,
+{
+    fn get_sol_conf(&self) -> usize {
+        self.0.get_sol_conf()
+    }
+
+    fn evaluate(&mut self, sol: &mut GenericSol) -> f64 {
+        self.0.evaluate(sol)
+    }
+
+    fn demonstrate(&self, sol: &GenericSol) {
+        self.0.demonstrate(sol)
+    }
+
+    fn print_state(&self) {
+        self.0.print_state()
+    }
+
+    fn get_frames(&self, sol: &GenericSol) -> Vec<DrawInstruction> {
+        self.0.get_frames(sol)
+    }
+
+    fn box_clone(&self) -> Box<dyn DynProblem> {
+        Box::new(self.clone())
+    }
+}
+
+/// The config of a problem, its real type is only known by the problem's entry.
+pub type AnyConfig = Arc<dyn Any + Send + Sync>;
+
+type Constructor = Box<dyn Fn(&mut GenRng, &AnyConfig) -> Box<dyn DynProblem> + Send + Sync>;
+type Parser = Box<dyn Fn(&str) -> Result<AnyConfig, String> + Send + Sync>;
+
+/// How to create and configure a registered problem.
+pub struct ProblemEntry {
+    name: String,
+    drawable: bool,
+    create: Constructor,
+    parse: Parser,
+}
+
+impl ProblemEntry {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_drawable(&self) -> bool {
+        self.drawable
+    }
+}
+
+/// Maps the names of the problems to their entries.
+pub struct ProblemRegistry {
+    entries: Vec<ProblemEntry>,
+}
+
+impl ProblemRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        ProblemRegistry { entries: vec![] }
+    }
+
+    /// Creates a registry with all the problems of this crate.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register_drawable::<MazeProblem>("maze", parse_size);
+        registry.register_drawable::<TurnAroundProblem>("turnaround", parse_size);
+        registry.register::<LinearEquationProblem>("lineareq", parse_size);
+        registry.register_drawable::<WallJumpProblem>("walljump", parse_size);
+        registry.register::<EasyProblem>("easy", parse_size);
+        registry.register::<EasyStep>("easystep", parse_size);
+        registry.register::<AllProblemsCompilation>("all", parse_size);
+        registry
+    }
+
+    /// Registers a problem, replacing any problem with the same name.
+    pub fn register<P>(
+        &mut self,
+        name: &str,
+        parse: fn(&str) -> Result<P::ProblemConfig, String>,
+    ) where
+        P: SingleStepProblem<Sol = GenericSol> + Clone + 'static,
+        P::ProblemConfig: Sync + 'static,
+    {
+        self.insert(ProblemEntry {
+            name: String::from(name),
+            drawable: false,
+            create: Box::new(
+                |xsr: &mut GenRng, conf: &AnyConfig| -> Box<dyn DynProblem> {
+                    Box::new(Plain(P::random(xsr, downcast::<P::ProblemConfig>(conf))))
+                },
+            ),
+            parse: Box::new(move |s: &str| -> Result<AnyConfig, String> {
+                parse(s).map(|c| Arc::new(c) as AnyConfig)
+            }),
+        });
+    }
+
+    /// Registers a problem that can be drawn, replacing any problem with the same name.
+    pub fn register_drawable<P>(
+        &mut self,
+        name: &str,
+        parse: fn(&str) -> Result<P::ProblemConfig, String>,
+    ) where
+        P: SingleStepDrawable<Sol = GenericSol> + Clone + 'static,
+        P::ProblemConfig: Sync + 'static,
+    {
+        self.insert(ProblemEntry {
+            name: String::from(name),
+            drawable: true,
+            create: Box::new(
+                |xsr: &mut GenRng, conf: &AnyConfig| -> Box<dyn DynProblem> {
+                    Box::new(Drawn(P::random(xsr, downcast::<P::ProblemConfig>(conf))))
+                },
+            ),
+            parse: Box::new(move |s: &str| -> Result<AnyConfig, String> {
+                parse(s).map(|c| Arc::new(c) as AnyConfig)
+            }),
+        });
+    }
+
+    fn insert(&mut self, entry: ProblemEntry) {
+        self.entries.retain(|e| e.name != entry.name);
+        self.entries.push(entry);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ProblemEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    pub fn drawable_names(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|e| e.drawable)
+            .map(|e| e.name.as_str())
+            .collect()
+    }
+
+    /// Parses the config of the named problem.
+    pub fn parse(&self, name: &str, conf: &str) -> Result<ProblemChoice, String> {
+        let entry = self.get(name).ok_or_else(|| {
+            format!(
+                "unknown problem : {}, known problems are : {}",
+                name,
+                self.names().join(", ")
+            )
+        })?;
+        let config = (entry.parse)(conf)
+            .map_err(|e| format!("invalid config for {} : {}", name, e))?;
+        Ok(ProblemChoice {
+            name: String::from(name),
+            text: String::from(conf.trim()),
+            config,
+        })
+    }
+
+    /// Creates a random instance of the chosen problem.
+    pub fn create(&self, choice: &ProblemChoice, xsr: &mut GenRng) -> RegisteredProblem {
+        let entry = self
+            .get(&choice.name)
+            .expect("the choice was parsed by another registry");
+        RegisteredProblem {
+            name: choice.name.clone(),
+            inner: (entry.create)(xsr, &choice.config),
+        }
+    }
+}
+
+/// Parses the config of the problems that only need a size.
+pub fn parse_size(s: &str) -> Result<usize, String> {
+    match s.trim().parse::<usize>() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(format!("expected a positive size, got {}", s)),
+    }
+}
+
+fn downcast<C: 'static>(conf: &AnyConfig) -> &C {
+    conf.downcast_ref::<C>()
+        .expect("the config was not parsed by this problem")
+}
+
+/// A problem and its parsed config.
+#[derive(Clone)]
+pub struct ProblemChoice {
+    name: String,
+    /// The config as it was given.
+    text: String,
+    config: AnyConfig,
+}
+
+impl ProblemChoice {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The problems to learn on, every random problem is one of them taken uniformly.
+#[derive(Clone)]
+pub struct ProblemMix {
+    registry: Arc<ProblemRegistry>,
+    choices: Vec<ProblemChoice>,
+}
+
+impl ProblemMix {
+    /// Parses a list of problems : "maze:20,lineareq:5,turnaround".
+    /// The problems without config use the default one.
+    pub fn parse(
+        registry: Arc<ProblemRegistry>,
+        spec: &str,
+        default_conf: &str,
+    ) -> Result<Self, String> {
+        let choices = spec
+            .split(',')
+            .map(|p| {
+                let mut parts = p.splitn(2, ':');
+                let name = parts.next().unwrap_or("").trim();
+                let conf = parts.next().unwrap_or(default_conf);
+                registry.parse(name, conf)
+            })
+            .collect::<Result<Vec<ProblemChoice>, String>>()?;
+        Ok(ProblemMix { registry, choices })
+    }
+
+    /// Parses the problems of the config, the ones without config get the size of the config.
+    pub fn from_config(registry: Arc<ProblemRegistry>, config: &Config) -> Result<Self, String> {
+        Self::parse(
+            registry,
+            &config.problem.name,
+            &config.problem.size.to_string(),
+        )
+    }
+
+    pub fn registry(&self) -> &Arc<ProblemRegistry> {
+        &self.registry
+    }
+
+    pub fn choices(&self) -> &Vec<ProblemChoice> {
+        &self.choices
+    }
+
+    /// The same mixture with the sizes multiplied by the fraction, for the problems whose config
+    /// is only a size.
+    pub fn scaled(&self, fraction: f64) -> Self {
+        let choices = self
+            .choices
+            .iter()
+            .map(|c| match c.text.parse::<usize>() {
+                Ok(size) => {
+                    // the smallest problems are degenerate, a maze of 1 has nothing to find
+                    let size = ((size as f64 * fraction).round() as usize).max(2);
+                    self.registry
+                        .parse(&c.name, &size.to_string())
+                        .unwrap_or_else(|_| c.clone())
+                }
+                Err(_) => c.clone(),
+            })
+            .collect();
+        ProblemMix {
+            registry: self.registry.clone(),
+            choices,
+        }
+    }
+
+    /// Tells if every problem of the mixture can be drawn.
+    pub fn is_drawable(&self) -> bool {
+        self.choices.iter().all(|c| {
+            self.registry
+                .get(&c.name)
+                .map(|e| e.is_drawable())
+                .unwrap_or(false)
+        })
+    }
+}
+
+impl Debug for ProblemMix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = self
+            .choices
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<&str>>();
+        write!(f, "ProblemMix({})", names.join(","))
+    }
+}
+
+/// A problem chosen at runtime.
+#[derive(Debug)]
+pub struct RegisteredProblem {
+    name: String,
+    inner: Box<dyn DynProblem>,
+}
+
+impl RegisteredProblem {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Clone for RegisteredProblem {
+    fn clone(&self) -> Self {
+        RegisteredProblem {
+            name: self.name.clone(),
+            inner: self.inner.box_clone(),
+        }
+    }
+}
+
+impl GenericProblem for RegisteredProblem {
+    type ProblemConfig = ProblemMix;
+
+    fn random(xsr: &mut GenRng, conf: &ProblemMix) -> Self {
+        let index = xsr.gen_range(0, conf.choices.len());
+        conf.registry.create(&conf.choices[index], xsr)
+    }
+
+    fn print_state(&self) {
+        self.inner.print_state()
+    }
+}
+
+impl SingleStepProblem for RegisteredProblem {
+    type Sol = GenericSol;
+
+    fn get_sol_conf(&self) -> <<Self as SingleStepProblem>::Sol as Solution>::SolConfig {
+        self.inner.get_sol_conf()
+    }
+
+    fn evaluate(&mut self, sol: &mut Self::Sol) -> f64 {
+        self.inner.evaluate(sol)
+    }
+
+    fn demonstrate(&self, sol: &Self::Sol) {
+        self.inner.demonstrate(sol)
+    }
+}
+
+impl SingleStepDrawable for RegisteredProblem {
+    fn get_frames(&self, sol: &Self::Sol) -> Vec<DrawInstruction> {
+        self.inner.get_frames(sol)
+    }
+}
@@ -4,10 +4,11 @@
 //! -1.0 for every unit of distance at the end
 //!
 //! Level : Very Easy
-use crate::graphics::DrawInstruction;
 use crate::graphics::SingleStepDrawable;
+use crate::graphics::{rgb, rgba, DrawInstruction, Point, Shape};
 use crate::problems::{GenericProblem, GenericSol, SingleStepProblem, Solution};
-use rand::{distributions::Standard, prelude::ThreadRng, Rng};
+use crate::random::GenRng;
+use rand::{distributions::Standard, Rng};
 use rulinalg::vector::Vector;
 
 pub const ARENA_SIZE: f64 = 100.0;
@@ -35,9 +36,13 @@ impl TurnAroundProblem {
     ) -> f64 {
         let mut current_pos = self.initial_pos.clone();
         let mut current_speed = self.initial_speed.clone();
+        let mut trajectory = vec![];
         for i in 0..14 {
             match opt {
-                RunOption::Draw => frames.push(self.get_frame(&current_pos)),
+                RunOption::Draw => {
+                    trajectory.push(Self::center(&current_pos));
+                    frames.push(self.get_frame(&current_pos, &trajectory));
+                }
                 RunOption::Verbose => self.print_state(),
                 RunOption::None => (),
             }
@@ -52,26 +57,46 @@ impl TurnAroundProblem {
                 .sum::<f64>()
     }
 
-    fn get_frame(&self, pos: &Vector<f64>) -> DrawInstruction {
-        DrawInstruction::Frame(vec![
-            [
-                pos[0] + ARENA_SIZE,
-                pos[1] + ARENA_SIZE,
-                POD_SIZE,
-                POD_SIZE,
-                0.0,
-                1.0,
-                0.0,
-            ],
-            [
-                self.initial_pos[0] + ARENA_SIZE,
-                self.initial_pos[1] + ARENA_SIZE,
-                POD_SIZE,
-                POD_SIZE,
-                1.0,
-                0.0,
-                0.0,
-            ],
+    /// Where the center of the pod is drawn.
+    fn center(pos: &Vector<f64>) -> Point {
+        [
+            pos[0] + ARENA_SIZE + POD_SIZE / 2.0,
+            pos[1] + ARENA_SIZE + POD_SIZE / 2.0,
+        ]
+    }
+
+    /// Draws the starting point in red, the trajectory so far and the pod in green.
+    fn get_frame(&self, pos: &Vector<f64>, trajectory: &[Point]) -> DrawInstruction {
+        let distance = (pos - &self.initial_pos)
+            .iter()
+            .map(|p| p.abs())
+            .sum::<f64>();
+        DrawInstruction::Shapes(vec![
+            Shape::Circle {
+                center: Self::center(&self.initial_pos),
+                radius: POD_SIZE / 2.0,
+                color: rgba(1.0, 0.0, 0.0, 0.6),
+            }
+            .on(0),
+            Shape::Line {
+                points: trajectory.to_vec(),
+                width: 1.0,
+                color: rgba(0.2, 0.2, 0.8, 0.7),
+            }
+            .on(1),
+            Shape::Circle {
+                center: Self::center(pos),
+                radius: POD_SIZE / 2.0,
+                color: rgb(0.0, 1.0, 0.0),
+            }
+            .on(2),
+            Shape::Text {
+                pos: [2.0, 2.0],
+                height: 6.0,
+                text: format!("distance {:.1}", distance),
+                color: rgb(0.0, 0.0, 0.0),
+            }
+            .on(3),
         ])
     }
 }
@@ -79,7 +104,7 @@ impl TurnAroundProblem {
 impl GenericProblem for TurnAroundProblem {
     type ProblemConfig = usize;
 
-    fn random(xsr: &mut ThreadRng, _conf: &usize) -> Self {
+    fn random(xsr: &mut GenRng, _conf: &usize) -> Self {
         TurnAroundProblem {
             initial_pos: xsr
                 .sample_iter(&Standard)
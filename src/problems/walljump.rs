@@ -13,8 +13,21 @@
 //!
 //!
 //! Level : Very Easy
+use crate::graphics::{rgb, rgba, DrawInstruction, Point, Shape, SingleStepDrawable};
 use crate::problems::{GenericProblem, GenericSol, SingleStepProblem, Solution};
-use rand::{prelude::ThreadRng, Rng};
+use crate::random::GenRng;
+use rand::Rng;
+
+/// The height of the drawn world, the wall is at most 15 high.
+const WORLD_HEIGHT: usize = 16;
+/// The width of the drawn world, it grows if the player goes farther.
+const WORLD_WIDTH: usize = 50;
+
+enum RunOption {
+    Verbose,
+    None,
+    Draw,
+}
 
 /// The starting position is between 0 and 10, the starting speed is between -5 and 5
 #[derive(Clone, Debug)]
@@ -34,15 +47,25 @@ impl WallJumpProblem {
         }
     }
 
-    fn play(&self, sol: &<Self as SingleStepProblem>::Sol, verbose: bool) -> f64 {
+    fn play(
+        &self,
+        sol: &<Self as SingleStepProblem>::Sol,
+        opt: RunOption,
+        frames: &mut Vec<DrawInstruction>,
+    ) -> f64 {
         let mut pos = vec![0.0, 0.0];
         let mut speed_up = 0.0;
         //println!("wall is {}m height at pos : {}", self.wall_height, self.wall_pos);
         let mut mv_r = 0.0;
+        let mut trajectory = vec![];
         for i in 0..25 {
-            if verbose {
+            if let RunOption::Verbose = opt {
                 self.print_pos(&pos);
             }
+            if let RunOption::Draw = opt {
+                trajectory.push(Self::center(&pos));
+                frames.push(self.get_frame(&trajectory));
+            }
             mv_r += Self::validate(sol.1[i]) / 2.0;
             mv_r *= 0.9;
             let mv_up = sol.1[i * 2];
@@ -77,6 +100,11 @@ impl WallJumpProblem {
         pos[0]
     }
 
+    /// Where the center of the player is drawn.
+    fn center(pos: &Vec<f64>) -> Point {
+        [pos[0] + 0.5, WORLD_HEIGHT as f64 - 1.0 - pos[1]]
+    }
+
     fn print_pos(&self, pos: &Vec<f64>) {
         for p_x in 0..11 {
             let x = 10 - p_x;
@@ -95,12 +123,86 @@ impl WallJumpProblem {
             self.wall_pos, self.wall_height, pos
         );
     }
+
+    /// Draws the ground, the wall in black with its height, the jumps so far and the player in
+    /// green.
+    fn get_frame(&self, trajectory: &[Point]) -> DrawInstruction {
+        let ground = WORLD_HEIGHT as f64 - 0.5;
+        let top = ground - self.wall_height;
+        let mut shapes = vec![
+            Shape::Rect {
+                pos: [0.0, ground],
+                size: [1000.0, 0.5],
+                color: rgb(0.5, 0.3, 0.1),
+            }
+            .on(0),
+            Shape::Polygon {
+                points: vec![
+                    [self.wall_pos, ground],
+                    [self.wall_pos, top],
+                    [self.wall_pos + 0.5, top],
+                    [self.wall_pos + 0.5, ground],
+                ],
+                color: rgb(0.0, 0.0, 0.0),
+            }
+            .on(0),
+            Shape::Text {
+                pos: [self.wall_pos + 1.0, top],
+                height: 1.0,
+                text: format!("{:.1}", self.wall_height),
+                color: rgb(0.0, 0.0, 0.0),
+            }
+            .on(0),
+            Shape::Line {
+                points: trajectory.to_vec(),
+                width: 0.2,
+                color: rgba(0.0, 0.6, 0.0, 0.5),
+            }
+            .on(1),
+        ];
+        if let Some(&center) = trajectory.last() {
+            shapes.push(
+                Shape::Circle {
+                    center,
+                    radius: 0.5,
+                    color: rgb(0.0, 1.0, 0.0),
+                }
+                .on(2),
+            );
+        }
+        DrawInstruction::Shapes(shapes)
+    }
+
+    /// The farthest the player goes in the frames.
+    fn farthest(frames: &[DrawInstruction]) -> f64 {
+        frames
+            .iter()
+            .filter_map(|f| match f {
+                DrawInstruction::Shapes(shapes) => shapes.iter().find_map(|s| match s.shape {
+                    Shape::Circle { center, .. } => Some(center[0]),
+                    _ => None,
+                }),
+                _ => None,
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+impl SingleStepDrawable for WallJumpProblem {
+    fn get_frames(&self, sol: &<Self as SingleStepProblem>::Sol) -> Vec<DrawInstruction> {
+        let mut frames = Vec::new();
+        self.play(sol, RunOption::Draw, &mut frames);
+        let width = WORLD_WIDTH.max(Self::farthest(&frames).ceil() as usize + 2);
+        let mut ret = vec![DrawInstruction::WorldSize([width, WORLD_HEIGHT])];
+        ret.extend(frames);
+        ret
+    }
 }
 
 impl GenericProblem for WallJumpProblem {
     type ProblemConfig = usize;
 
-    fn random(xsr: &mut ThreadRng, _conf: &usize) -> Self {
+    fn random(xsr: &mut GenRng, _conf: &usize) -> Self {
         WallJumpProblem {
             wall_pos: 2.0 + xsr.gen::<f64>() * 10.0,
             wall_height: 5.0 + xsr.gen::<f64>() * 10.0,
@@ -121,10 +223,10 @@ impl SingleStepProblem for WallJumpProblem {
 
     /// Plays a full game.
     fn evaluate(&mut self, sol: &mut Self::Sol) -> f64 {
-        self.play(sol, false)
+        self.play(sol, RunOption::None, &mut Vec::new())
     }
 
     fn demonstrate(&self, sol: &<Self as SingleStepProblem>::Sol) {
-        self.play(sol, true);
+        self.play(sol, RunOption::Verbose, &mut Vec::new());
     }
 }
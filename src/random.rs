@@ -0,0 +1,29 @@
+//! The random module
+//! Every random choice of the problems and of the genetic algorithms goes through a seedable
+//! generator so that a run can be replayed from its seed.
+use rand::prelude::thread_rng;
+use rand::prng::XorShiftRng;
+use rand::SeedableRng;
+
+/// The generator used everywhere in the crate.
+pub type GenRng = XorShiftRng;
+
+/// Creates a generator from a seed, the same seed always gives the same generator.
+pub fn seeded(seed: u64) -> GenRng {
+    let mut bytes = [0u8; 16];
+    for i in 0..8 {
+        bytes[i] = (seed >> (8 * i)) as u8;
+        bytes[i + 8] = (seed.rotate_left(32) >> (8 * i)) as u8;
+    }
+    GenRng::from_seed(bytes)
+}
+
+/// Creates a generator seeded from the thread's entropy.
+pub fn from_entropy() -> GenRng {
+    GenRng::from_rng(thread_rng()).expect("could not seed the generator")
+}
+
+/// Creates a new independent generator whose seed is taken from the given one.
+pub fn derive(parent: &mut GenRng) -> GenRng {
+    GenRng::from_rng(parent).expect("could not seed the generator")
+}
@@ -1,24 +1,34 @@
 //! The reilearn module
+use crate::exploration::{Exploration, ExplorationSettings};
 use crate::graphics::{DrawInstruction, ManyStepDrawable};
-use crate::params::{MAX_GENETIC_ALG_GEN, MAX_ITER, TEST_DATA_SIZE};
+use crate::history::{History, HistorySettings};
+use crate::netfile;
+use crate::observer::{Event, ObserverHandle};
+use crate::params::*;
 use crate::problems::ManyStepProblem;
+use crate::random::{self, GenRng};
+use crate::replay::{Example, ReplayBuffer, ReplaySettings};
 use lmsmw::Test;
 /// Contains methods to apply reinforcment learning to lmsmw.
 /// does not work like usual reinforcment learning due to the fact that it is not choosing
 /// in a discrete set of options.
 ///
 use lmsmw::{network::Network, ExamplesConfig, Learner};
-use rand::{
-    prelude::{thread_rng, ThreadRng},
-    Rng,
-};
+use rand::distributions::{Distribution, Normal};
+use rand::Rng;
 use rulinalg::vector::Vector;
+use serde::{Deserialize, Serialize};
 /// Represents a choice made by the neural network in a given situation.
 pub struct Choice {
     inputs: Vector<f64>,
-    #[allow(dead_code)]
     outputs: Vector<f64>,
     choice: Vector<f64>,
+    /// The outputs of the network after the action, they are learned as they were.
+    memory: Vector<f64>,
+    /// What the step brought, if the problem tells.
+    reward: Option<f64>,
+    /// How good the choice was : the discounted return from its step, or the playout's score.
+    credit: f64,
 }
 
 impl Choice {
@@ -27,6 +37,17 @@ impl Choice {
             inputs: inputs,
             outputs: outputs,
             choice: choice,
+            memory: Vector::new(vec![]),
+            reward: None,
+            credit: 0.0,
+        }
+    }
+    /// The test of into_good_test or into_bad_test, scored by the credit of the choice.
+    pub fn into_example(self, good: bool) -> Example {
+        let score = self.credit;
+        match good {
+            true => Example::new(self.into_good_test(), score),
+            false => Example::new(self.into_bad_test(), score),
         }
     }
     /// Reinforce the good moves.
@@ -34,7 +55,7 @@ impl Choice {
     pub fn into_good_test(self) -> Test {
         Test {
             inputs: self.inputs,
-            outputs: self.choice,
+            outputs: self.choice.iter().chain(self.memory.iter()).cloned().collect(),
         }
     }
     /// Don't reinforce the good moves.
@@ -42,19 +63,212 @@ impl Choice {
     pub fn into_bad_test(self) -> Test {
         Test {
             inputs: self.inputs,
-            outputs: self.choice.iter().map(|c| 1.0 - c).collect(),
+            outputs: self
+                .choice
+                .iter()
+                .map(|c| 1.0 - c)
+                .chain(self.memory.iter().cloned())
+                .collect(),
+        }
+    }
+    /// Moves the outputs toward the choice when the advantage is positive and away from it when
+    /// it is negative : the gradient of a gaussian policy's log probability, scaled by its
+    /// variance.
+    pub fn into_policy_test(self, advantage: f64, step: f64) -> Test {
+        let outputs = self
+            .outputs
+            .iter()
+            .zip(self.choice.iter())
+            .map(|(o, c)| (o + step * advantage * (c - o)).max(0.0).min(1.0))
+            .chain(self.memory.iter().cloned())
+            .collect();
+        Test {
+            inputs: self.inputs,
+            outputs,
         }
     }
 }
 
+/// How the network learns from its playouts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrainingScheme {
+    /// Imitates the best playouts of every problem and does the opposite of the worst ones.
+    EliteImitation,
+    /// Policy gradient : every choice is reinforced by how much better than the baseline its
+    /// playout was, the noise is gaussian.
+    Reinforce,
+    /// Natural evolution strategies on the weights of the network, scored like the test problems.
+    Evolution,
+    /// Tries several choices at every step, follows each one with the network for a few steps and
+    /// imitates the best one.
+    Branching,
+}
+
+impl Default for TrainingScheme {
+    fn default() -> Self {
+        TrainingScheme::EliteImitation
+    }
+}
+
+impl std::str::FromStr for TrainingScheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "elite_imitation" | "elite" => Ok(TrainingScheme::EliteImitation),
+            "reinforce" => Ok(TrainingScheme::Reinforce),
+            "evolution" | "nes" => Ok(TrainingScheme::Evolution),
+            "branching" => Ok(TrainingScheme::Branching),
+            _ => Err(format!("unknown training scheme : {}", s)),
+        }
+    }
+}
+
+/// What the scores of the playouts are compared to with the Reinforce scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyBaseline {
+    /// The average score of the playouts of the same problem.
+    Average,
+    /// A linear estimation of the score from the state, learned along.
+    Learned,
+}
+
+impl Default for PolicyBaseline {
+    fn default() -> Self {
+        PolicyBaseline::Average
+    }
+}
+
+/// How the score of a playout is made comparable between problems, by comparing it to the
+/// fixed parameters on the same problem.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// The scores as the problems give them.
+    Raw,
+    /// 1 for the score of the neutral outputs, 2 for a score better by the size of theirs.
+    Ratio,
+    /// The part of rank_baselines fixed parameters doing worse on the problem, in [0, 1] : the
+    /// neutral outputs and random ones.
+    Rank,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Normalization::Ratio
+    }
+}
+
 /// The different parameters for learning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct LearnParams {
-    nb_problems: usize,
-    test_per_prob: usize,
-    max_gen: usize,
-    starting_coef: f64,
-    coef_mod: f64,
-    percent_elite: f64,
+    /// Number of random problems played every generation.
+    pub nb_problems: usize,
+    /// Number of playouts on each of these problems.
+    pub test_per_prob: usize,
+    /// Maximum number of iterations of the learner when retraining.
+    pub max_gen: usize,
+    pub starting_coef: f64,
+    pub coef_mod: f64,
+    pub percent_elite: f64,
+    /// Number of problems the network is tested on, once at the end of a training.
+    pub test_data_size: usize,
+    /// Number of problems the network is scored on after every learning generation.
+    pub validation_data_size: usize,
+    /// A training stops after this many generations without a better validation score.
+    pub patience: usize,
+    /// Number of steps played when the problem does not know its maximum.
+    pub max_steps: usize,
+    /// Number of learning generations of a training run.
+    pub generations: usize,
+    pub grad_iters: usize,
+    pub grad_step: f64,
+    /// The gradient descent uses batches of this many examples.
+    pub grad_batch_size: usize,
+    /// Levenberg-Marquardt uses batches of this many examples.
+    pub lvbm_batch_size: usize,
+    pub lvbm_max_iters: usize,
+    /// The learner stops when reaching this score.
+    pub aim_score: f64,
+    pub scheme: TrainingScheme,
+    pub policy_baseline: PolicyBaseline,
+    /// How far the Reinforce targets move from the outputs for an advantage of 1.
+    pub policy_step: f64,
+    /// Learning rate of the learned baseline, in ]0, 2[.
+    pub baseline_rate: f64,
+    /// Number of perturbations of the weights tried by every evolution step, each one is also
+    /// tried in the opposite direction.
+    pub es_population: usize,
+    /// Number of new problems every perturbation is played on.
+    pub es_problems: usize,
+    /// Deviation of the perturbations.
+    pub es_sigma: f64,
+    pub es_step: f64,
+    pub exploration: ExplorationSettings,
+    /// What the network sees of the previous steps, it changes the size of its layers.
+    pub history: HistorySettings,
+    /// The examples kept from one generation to the next, not used by the evolution.
+    pub replay: ReplaySettings,
+    /// Discount of the future rewards, for the problems giving a reward every step.
+    pub gamma: f64,
+    /// Used for the playouts, the evolution, the validation and the test scores.
+    pub normalization: Normalization,
+    /// Number of fixed parameters the scores are ranked among with the rank normalization.
+    pub rank_baselines: usize,
+    /// Sizes of the training problems at every difficulty level, as fractions of the problem's
+    /// size, empty to always learn on the full size.
+    pub curriculum: Vec<f64>,
+    /// The validation score needed to leave each level but the last.
+    pub curriculum_thresholds: Vec<f64>,
+    /// Number of choices tried at every step by the branching, the network's and noisy ones.
+    pub branch_factor: usize,
+    /// Number of steps a branch is played before being scored.
+    pub branch_horizon: usize,
+}
+
+impl Default for LearnParams {
+    fn default() -> Self {
+        LearnParams {
+            nb_problems: NB_EXAMPLE_PROBLEMS,
+            test_per_prob: TESTS_PER_PROBLEM,
+            max_gen: MAX_GEN,
+            starting_coef: STARTING_COEF,
+            coef_mod: COEF_MODIFICATOR,
+            percent_elite: PERCENT_ELITE,
+            test_data_size: TEST_DATA_SIZE,
+            validation_data_size: VALIDATION_DATA_SIZE,
+            patience: PATIENCE,
+            max_steps: MAX_GENETIC_ALG_GEN,
+            generations: GENERATIONS,
+            grad_iters: GRAD_ITERS,
+            grad_step: GRAD_STEP,
+            grad_batch_size: GRAD_BATCH_SIZE,
+            lvbm_batch_size: LVBM_BATCH_SIZE,
+            lvbm_max_iters: MAX_ITER,
+            aim_score: AIM_SCORE,
+            scheme: TrainingScheme::default(),
+            policy_baseline: PolicyBaseline::default(),
+            policy_step: POLICY_STEP,
+            baseline_rate: BASELINE_RATE,
+            es_population: ES_POPULATION,
+            es_problems: ES_PROBLEMS,
+            es_sigma: ES_SIGMA,
+            es_step: ES_STEP,
+            exploration: ExplorationSettings::default(),
+            history: HistorySettings::default(),
+            replay: ReplaySettings::default(),
+            gamma: GAMMA,
+            normalization: Normalization::default(),
+            rank_baselines: RANK_BASELINES,
+            curriculum: vec![],
+            curriculum_thresholds: vec![],
+            branch_factor: BRANCH_FACTOR,
+            branch_horizon: BRANCH_HORIZON,
+        }
+    }
 }
 
 impl LearnParams {
@@ -73,86 +287,414 @@ impl LearnParams {
             starting_coef: starting_coef,
             coef_mod: coef_mod,
             percent_elite: percent_elite,
+            ..Self::default()
         }
     }
 }
 
+/// What a training did.
+#[derive(Debug, Clone)]
+pub struct TrainingReport {
+    /// Number of learning generations played.
+    pub generations: usize,
+    /// Average score of the kept network on the validation problems.
+    pub best_validation: f64,
+    /// Average score of the kept network on the test problems.
+    pub test_score: f64,
+}
+
 /// Contains the tests problems
+/// The network learns on new random problems, is chosen on the validation problems and is scored
+/// on the test problems.
 pub struct ReiLearn<P: ManyStepProblem> {
     test_problems: Vec<P>,
+    validation_problems: Vec<P>,
     net: Network,
-    random: ThreadRng,
+    random: GenRng,
     params: LearnParams,
     problem_confs: P::ProblemConfig,
+    /// The configs of the training problems from the easiest, see set_curriculum.
+    levels: Vec<P::ProblemConfig>,
+    level: usize,
+    observer: ObserverHandle,
     pub coef: f64,
+    /// Weights of the learned baseline, the last one is the bias.
+    baseline_weights: Vec<f64>,
+    exploration: Box<dyn Exploration>,
+    /// Scores of the playouts of the current generation.
+    playout_scores: Vec<f64>,
+    /// Best average score of the playouts of a generation.
+    best_playouts: Option<f64>,
+    /// The fixed outputs the scores are normalized against, the neutral ones first.
+    baseline_outputs: Vec<Vector<f64>>,
+    /// Scores of the baseline outputs on every validation problem.
+    validation_baselines: Vec<Vec<f64>>,
+    /// Scores of the baseline outputs on every test problem.
+    test_baselines: Vec<Vec<f64>>,
+    /// The least the ratio normalization divides by, see baseline_floor.
+    baseline_floor: f64,
+    /// The examples of the previous generations.
+    replay: ReplayBuffer,
+    /// The network with the best validation score of the training and that score.
+    best: Option<(Network, f64)>,
+    /// Set to end the training after the current generation.
+    stopping: bool,
 }
 
 impl<P: ManyStepProblem> ReiLearn<P> {
     pub fn new(net: Network, prob_conf: P::ProblemConfig, learn_param: LearnParams) -> Self {
-        let mut my_rand = thread_rng();
-        ReiLearn {
+        Self::with_rng(net, prob_conf, learn_param, random::from_entropy())
+    }
+
+    /// Creates the learner drawing its problems from the given generator.
+    pub fn with_rng(
+        net: Network,
+        prob_conf: P::ProblemConfig,
+        learn_param: LearnParams,
+        mut my_rand: GenRng,
+    ) -> Self {
+        let mut rl = ReiLearn {
             coef: learn_param.starting_coef,
-            test_problems: (0..TEST_DATA_SIZE)
+            baseline_weights: vec![0.0; net.layers()[0] + 1],
+            exploration: learn_param.exploration.build(learn_param.scheme),
+            replay: ReplayBuffer::new(learn_param.replay.capacity),
+            playout_scores: vec![],
+            best_playouts: None,
+            test_problems: (0..learn_param.test_data_size)
+                .map(|_| P::random(&mut my_rand, &prob_conf))
+                .collect(),
+            validation_problems: (0..learn_param.validation_data_size)
                 .map(|_| P::random(&mut my_rand, &prob_conf))
                 .collect(),
             net: (net),
             random: (my_rand),
             params: learn_param,
             problem_confs: prob_conf,
+            levels: vec![],
+            level: 0,
+            observer: ObserverHandle::console(),
+            baseline_outputs: vec![],
+            validation_baselines: vec![],
+            test_baselines: vec![],
+            baseline_floor: MIN_BASELINE_SCORE,
+            best: None,
+            stopping: false,
+        };
+        rl.baseline_outputs = rl.draw_baseline_outputs();
+        rl.validation_baselines = rl
+            .validation_problems
+            .iter()
+            .map(|p| rl.baseline_scores(p))
+            .collect();
+        rl.test_baselines = rl
+            .test_problems
+            .iter()
+            .map(|p| rl.baseline_scores(p))
+            .collect();
+        rl.baseline_floor = baseline_floor(&rl.validation_baselines);
+        rl
+    }
+
+    /// The neutral outputs, and for the rank normalization random fixed outputs.
+    fn draw_baseline_outputs(&mut self) -> Vec<Vector<f64>> {
+        let count = match self.params.normalization {
+            Normalization::Raw => 0,
+            Normalization::Ratio => 1,
+            Normalization::Rank => self.params.rank_baselines,
+        };
+        let size = *self.net.layers().last().unwrap();
+        let mut outputs = vec![];
+        for index in 0..count {
+            outputs.push(match index {
+                0 => self.neutral_outputs(),
+                _ => (0..size).map(|_| self.random.gen::<f64>()).collect(),
+            });
         }
+        outputs
+    }
+
+    /// Plays the problem with every baseline outputs.
+    /// Returns their scores.
+    fn baseline_scores(&self, p: &P) -> Vec<f64> {
+        self.baseline_outputs
+            .iter()
+            .map(|outputs| self.play_out(p, |_| outputs.clone()).evaluate())
+            .collect()
+    }
+
+    /// The score compared to the scores of the baselines on the same problem.
+    fn normalize(&self, score: f64, baselines: &[f64]) -> f64 {
+        normalize(self.params.normalization, score, baselines, self.baseline_floor)
+    }
+
+    /// What the rewards of the steps are multiplied by, to be on the same scale on every problem.
+    fn reward_scale(&self, baselines: &[f64]) -> f64 {
+        match baselines.first() {
+            Some(baseline) => 1.0 / baseline.abs().max(self.baseline_floor),
+            None => 1.0,
+        }
+    }
+
+    /// Sets the observer receiving the learning events, they are printed on the console by default.
+    pub fn set_observer(&mut self, observer: ObserverHandle) {
+        self.observer = observer;
     }
-    #[allow(dead_code)]
     pub fn get_net(&self) -> &Network {
         &self.net
     }
 
+    /// Makes the training problems go through the levels, from the first one. The next level
+    /// starts when the validation score reaches its threshold in curriculum_thresholds.
+    /// The validation and test problems keep the learner's config.
+    pub fn set_curriculum(&mut self, levels: Vec<P::ProblemConfig>) {
+        self.levels = levels;
+        self.level = 0;
+        if !self.levels.is_empty() {
+            self.observer.notify(Event::LevelChanged {
+                level: 0,
+                levels: self.levels.len(),
+            });
+        }
+    }
+
+    /// The difficulty level of the training problems, 0 without curriculum.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn get_params(&self) -> &LearnParams {
+        &self.params
+    }
+
     pub fn get_test_problems(&self) -> &Vec<P> {
         &self.test_problems
     }
 
+    pub fn get_validation_problems(&self) -> &Vec<P> {
+        &self.validation_problems
+    }
+
     #[allow(dead_code)]
     pub fn demonstrate(&self) {
         for p in self.test_problems.iter() {
             let mut prob = p.clone();
-            for _ in 0..p.max_step().unwrap_or(MAX_GENETIC_ALG_GEN) {
+            prob.set_observer(self.observer.clone());
+            let mut history = self.new_history(p);
+            for _ in 0..p.max_step().unwrap_or(self.params.max_steps) {
                 prob.print_state();
-                let inputs = prob.get_state();
-                prob.make_step(&self.net.feed_forward(&inputs));
+                let action = history.act(&self.net, prob.get_state());
+                prob.make_step(&action);
                 if prob.is_solved() {
                     break;
                 }
             }
             prob.print_state();
-            println!("demo \n")
+            self.observer.notify(Event::DemoPlayed {
+                score: prob.evaluate(),
+            });
         }
     }
 
     pub fn demonstrate_on(&self, p: P) {
         let mut prob = p.clone();
-        for _ in 0..p.max_step().unwrap_or(MAX_GENETIC_ALG_GEN) {
+        prob.set_observer(self.observer.clone());
+        let mut history = self.new_history(&p);
+        for _ in 0..p.max_step().unwrap_or(self.params.max_steps) {
             prob.print_state();
-            let inputs = prob.get_state();
-            prob.make_step(&self.net.feed_forward(&inputs));
+            let action = history.act(&self.net, prob.get_state());
+            prob.make_step(&action);
             if prob.is_solved() {
                 break;
             }
         }
         prob.print_state();
-        println!("demo \n")
+        self.observer.notify(Event::DemoPlayed {
+            score: prob.evaluate(),
+        });
+    }
+
+    /// Plays the problems choosing the outputs with the given function.
+    /// Returns the sum of the scores, normalized against the scores of the baselines on them.
+    fn play_problems<F>(&self, problems: &[P], baselines: &[Vec<f64>], mut choose: F) -> f64
+    where
+        F: FnMut(&Vector<f64>) -> Vector<f64>,
+    {
+        problems
+            .iter()
+            .zip(baselines.iter())
+            .map(|(p, b)| self.normalize(self.play_out(p, &mut choose).evaluate(), b))
+            .sum()
+    }
+
+    /// Plays a copy of the problem until it is solved, choosing the outputs with the given
+    /// function from the inputs of the network. Returns the copy.
+    fn play_out<F: FnMut(&Vector<f64>) -> Vector<f64>>(&self, p: &P, mut choose: F) -> P {
+        let mut prob = p.clone();
+        let mut history = self.new_history(p);
+        for _ in 0..p.max_step().unwrap_or(self.params.max_steps) {
+            let observation = prob.get_state();
+            let outputs = choose(&history.inputs(&observation));
+            let action = history.action_of(&outputs);
+            prob.make_step(&action);
+            history.record(observation, &action, &outputs);
+            if prob.is_solved() {
+                break;
+            }
+        }
+        prob
+    }
+
+    /// What the network remembers before the first step of the problem.
+    pub fn new_history(&self, p: &P) -> History {
+        History::new(&self.params.history, p.output_space(), p.input_space())
+    }
+
+    /// The outputs keeping everything as it is, 0.5 everywhere.
+    pub fn neutral_outputs(&self) -> Vector<f64> {
+        Vector::new(vec![0.5; *self.net.layers().last().unwrap()])
     }
 
     pub fn run_on_test_example(&self) -> f64 {
-        let mut score = 0.0;
-        for p in self.test_problems.iter() {
-            let mut prob = p.clone();
-            for _ in 0..p.max_step().unwrap_or(MAX_GENETIC_ALG_GEN) {
-                let inputs = prob.get_state();
-                prob.make_step(&self.net.feed_forward(&inputs));
-                if prob.is_solved() {
-                    break;
-                }
+        let score = self.play_problems(&self.test_problems, &self.test_baselines, |inputs| {
+            self.net.feed_forward(inputs)
+        });
+        self.observer.notify(Event::TestScoreComputed {
+            score: score / self.test_problems.len() as f64,
+        });
+        score
+    }
+
+    /// Plays the validation problems with the network.
+    /// Returns the average score.
+    pub fn run_on_validation(&self) -> f64 {
+        let score = self.play_problems(
+            &self.validation_problems,
+            &self.validation_baselines,
+            |inputs| self.net.feed_forward(inputs),
+        ) / self.validation_problems.len() as f64;
+        self.observer
+            .notify(Event::ValidationScoreComputed { score });
+        score
+    }
+
+    /// Plays the test problems choosing the outputs with the given function instead of the
+    /// network, to compare other supervisors with it.
+    /// Returns the average score, normalized like the network's.
+    pub fn score_on_test<F>(&self, choose: F) -> f64
+    where
+        F: FnMut(&Vector<f64>) -> Vector<f64>,
+    {
+        self.play_problems(&self.test_problems, &self.test_baselines, choose)
+            / self.test_problems.len() as f64
+    }
+
+    /// Number of outputs of the network, the action and the memory.
+    pub fn output_size(&self) -> usize {
+        *self.net.layers().last().unwrap()
+    }
+
+    /// Plays the validation problems without the network, always giving 0.5 as outputs : for the
+    /// genetic algorithms it keeps the starting parameters.
+    /// Returns the sum of the scores.
+    pub fn run_baseline(&self) -> f64 {
+        let outputs = self.neutral_outputs();
+        let score = self.play_problems(
+            &self.validation_problems,
+            &self.validation_baselines,
+            |_| outputs.clone(),
+        );
+        self.observer.notify(Event::BaselineComputed {
+            score: score / self.validation_problems.len() as f64,
+        });
+        score
+    }
+
+    /// Learns until the validation score has not improved for patience generations, for
+    /// generations generations or until stop_training, then keeps the network with the best
+    /// validation score and scores it once on the test problems.
+    /// after_generation is called after every generation with its number and validation score,
+    /// once the best network is updated.
+    pub fn train<F>(&mut self, mut after_generation: F) -> TrainingReport
+    where
+        F: FnMut(&mut Self, usize, f64),
+    {
+        let start = self.run_on_validation();
+        self.best = Some((self.net.clone(), start));
+        self.stopping = false;
+        let (mut generations, mut since_best) = (0, 0);
+        while generations < self.params.generations
+            && since_best < self.params.patience
+            && !self.stopping
+        {
+            self.next_gen();
+            generations += 1;
+            let level = self.level;
+            let score = self.validate();
+            if self.level != level {
+                // the harder problems need time before improving again
+                since_best = 0;
+            }
+            if score > self.best_network().map_or(start, |(_, best)| best) {
+                self.best = Some((self.net.clone(), score));
+                since_best = 0;
+            } else {
+                since_best += 1;
             }
-            score += prob.evaluate();
+            after_generation(self, generations, score);
+        }
+        let (net, best_validation) = self.best.clone().unwrap();
+        self.observer.notify(Event::TrainingStopped {
+            generations,
+            best_validation,
+        });
+        self.net = net;
+        TrainingReport {
+            generations,
+            best_validation,
+            test_score: self.run_on_test_example() / self.test_problems.len() as f64,
+        }
+    }
+
+    /// Ends the training after the current generation, from the callback of train.
+    pub fn stop_training(&mut self) {
+        self.stopping = true;
+    }
+
+    /// The network with the best validation score of the last training and that score.
+    pub fn best_network(&self) -> Option<(&Network, f64)> {
+        self.best.as_ref().map(|(net, score)| (net, *score))
+    }
+
+    /// Plays the first validation problem like the baseline, from the same state as the demo.
+    /// Returns the problem as it is at the end.
+    pub fn baseline_demo(&self) -> P {
+        self.baseline_demo_on(self.validation_problems.first().unwrap())
+    }
+
+    /// Plays the problem like the baseline, returns it as it is at the end.
+    pub fn baseline_demo_on(&self, p: &P) -> P {
+        let outputs = self.neutral_outputs();
+        self.play_out(p, |_| outputs.clone())
+    }
+
+    /// Draws a new problem, like the ones the network learns on.
+    pub fn random_problem(&mut self) -> P {
+        let conf = self.levels.get(self.level).unwrap_or(&self.problem_confs);
+        P::random(&mut self.random, conf)
+    }
+
+    /// Scores the network on the validation problems, and moves the training problems to the
+    /// next level if the score is high enough.
+    /// Returns the average score.
+    pub fn validate(&mut self) -> f64 {
+        let score = self.run_on_validation();
+        let threshold = self.params.curriculum_thresholds.get(self.level);
+        if self.level + 1 < self.levels.len() && threshold.map_or(false, |&t| score >= t) {
+            self.level += 1;
+            self.observer.notify(Event::LevelChanged {
+                level: self.level,
+                levels: self.levels.len(),
+            });
         }
         score
     }
@@ -164,125 +706,615 @@ impl<P: ManyStepProblem> ReiLearn<P> {
         self.random.shuffle(&mut my_tests);
         let net = Learner::new(ExamplesConfig::Ready(my_tests.clone()), layers)
             .set_net(self.net.clone())
-            .gradient_descent_iters(20)
-            .gradient_descent_step(0.01)
-            .gradient_descent_nb_batches(tests.len() / 200)
-            .lvbm_nb_batches(tests.len() / 50)
-            .aim_score(0.05) // try to go until 0.05 score
+            .gradient_descent_iters(self.params.grad_iters)
+            .gradient_descent_step(self.params.grad_step)
+            .gradient_descent_nb_batches(tests.len() / self.params.grad_batch_size)
+            .lvbm_nb_batches(tests.len() / self.params.lvbm_batch_size)
+            .aim_score(self.params.aim_score)
             .max_iter(self.params.max_gen)
-            .lvbm_max_iters(MAX_ITER)
+            .lvbm_max_iters(self.params.lvbm_max_iters)
             .start();
         self.net = net;
     }
 
     /// Performs a next iteration of testing -> reinforcing the network.
     pub fn next_gen(&mut self) {
-        let mut tests = vec![];
+        if self.params.scheme == TrainingScheme::Evolution {
+            let candidates = self.evolve();
+            self.observer.notify(Event::NetworkRetrained {
+                examples: candidates,
+                coef: self.params.es_sigma,
+            });
+            return;
+        }
+        let mut fresh = vec![];
         for _ in 0..self.params.nb_problems {
-            let prob = P::random(&mut self.random, &self.problem_confs);
-            tests.append(&mut self.gen_tests_for_prob(prob));
+            let prob = self.random_problem();
+            match self.params.scheme {
+                TrainingScheme::Branching => fresh.append(&mut self.gen_branching_tests(prob)),
+                _ => fresh.append(&mut self.gen_tests_for_prob(prob)),
+            }
         }
-        println!("training on {} examples", &tests.len());
+        let settings = &self.params.replay;
+        let mut tests = self.replay.sample(
+            settings.replayed_for(fresh.len()),
+            settings.priority,
+            &mut self.random,
+        );
+        tests.extend(fresh.iter().map(|example| example.test.clone()));
+        self.replay.extend(fresh);
         self.reinforce(&tests);
-        self.coef *= self.params.coef_mod;
+        self.observer.notify(Event::NetworkRetrained {
+            examples: tests.len(),
+            coef: self.coef,
+        });
+        let scores = std::mem::replace(&mut self.playout_scores, vec![]);
+        let average = scores.iter().sum::<f64>() / scores.len().max(1) as f64;
+        self.coef = self.params.exploration.next_coef(
+            self.coef,
+            self.params.coef_mod,
+            self.params.starting_coef,
+            average,
+            self.best_playouts,
+        );
+        self.best_playouts = Some(self.best_playouts.map_or(average, |b| b.max(average)));
+    }
+
+    /// One step of natural evolution strategies on the weights of the network.
+    /// Every perturbation and its opposite are played on the same new problems, the weights move
+    /// toward the perturbations depending on the rank of their total score.
+    /// Returns the number of networks tried.
+    pub fn evolve(&mut self) -> usize {
+        let weights = netfile::flatten(&self.net);
+        let problems = (0..self.params.es_problems)
+            .map(|_| self.random_problem())
+            .collect::<Vec<P>>();
+        let baselines = problems
+            .iter()
+            .map(|p| self.baseline_scores(p))
+            .collect::<Vec<Vec<f64>>>();
+        let normal = Normal::new(0.0, 1.0);
+        let sigma = self.params.es_sigma;
+        let (mut noises, mut scores) = (vec![], vec![]);
+        for _ in 0..self.params.es_population {
+            let noise = weights
+                .iter()
+                .map(|_| normal.sample(&mut self.random))
+                .collect::<Vec<f64>>();
+            for &sign in [1.0, -1.0].iter() {
+                let candidate = weights
+                    .iter()
+                    .zip(noise.iter())
+                    .map(|(w, n)| w + sign * sigma * n)
+                    .collect::<Vec<f64>>();
+                let net = netfile::unflatten(&self.net, &candidate)
+                    .expect("the candidate has the size of the network");
+                let score = self.play_problems(&problems, &baselines, |inputs| {
+                    net.feed_forward(inputs)
+                });
+                scores.push(score);
+            }
+            noises.push(noise);
+        }
+        let ranks = centered_ranks(&scores);
+        let step = self.params.es_step / (scores.len() as f64 * sigma);
+        let mut updated = weights;
+        for (index, noise) in noises.iter().enumerate() {
+            let weight = ranks[2 * index] - ranks[2 * index + 1];
+            for (w, n) in updated.iter_mut().zip(noise.iter()) {
+                *w += step * weight * n;
+            }
+        }
+        self.net = netfile::unflatten(&self.net, &updated)
+            .expect("the update has the size of the network");
+        scores.len()
     }
 
     /// Tests the network several times on the problem and returns the reinforcment directives for
     /// this problem.
-    pub fn gen_tests_for_prob(&mut self, prob: P) -> (Vec<Test>) {
+    pub fn gen_tests_for_prob(&mut self, prob: P) -> Vec<Example> {
+        let baselines = self.baseline_scores(&prob);
         let mut results_prob = vec![];
         for _ in 0..self.params.test_per_prob {
             let cloned = prob.clone();
-            results_prob.push(self.play_problem(cloned));
+            results_prob.push(self.play_problem(cloned, &baselines));
+        }
+        match self.params.scheme {
+            TrainingScheme::Reinforce => self.gen_policy_tests(results_prob),
+            _ => self.gen_tests_from_choices(results_prob),
+        }
+    }
+
+    /// Plays the problem choosing at every step the best of branch_factor candidates : the
+    /// network's outputs and noisy versions of them, each one followed by the network for
+    /// branch_horizon steps and scored there.
+    /// Returns a test per step, labelled with the best candidate and scored by its branch.
+    pub fn gen_branching_tests(&mut self, problem: P) -> Vec<Example> {
+        use ordered_float::OrderedFloat;
+        let baselines = self.baseline_scores(&problem);
+        let mut prob = problem;
+        let mut history = self.new_history(&prob);
+        let mut tests = vec![];
+        self.exploration.reset();
+        for _ in 0..prob.max_step().unwrap_or(self.params.max_steps) {
+            let observation = prob.get_state();
+            let inputs = history.inputs(&observation);
+            let outputs = self.net.feed_forward(&inputs);
+            let action = history.action_of(&outputs);
+            let mut candidates = vec![action.clone()];
+            for _ in 1..self.params.branch_factor {
+                candidates.push(self.modify_outputs(&action));
+            }
+            let rngs = candidates
+                .iter()
+                .map(|_| random::derive(&mut self.random))
+                .collect::<Vec<GenRng>>();
+            let (branch_score, best, next, next_history) = candidates
+                .into_iter()
+                .zip(rngs)
+                .map(|(candidate, rng)| {
+                    // every branch draws its own numbers, the kept one goes on with them
+                    let mut next = prob.clone();
+                    next.reseed(rng);
+                    next.make_step(&candidate);
+                    let mut next_history = history.clone();
+                    next_history.record(observation.clone(), &candidate, &outputs);
+                    let (mut branch, mut branch_history) = (next.clone(), next_history.clone());
+                    self.roll_out(
+                        &mut branch,
+                        &mut branch_history,
+                        self.params.branch_horizon - 1,
+                    );
+                    (branch.evaluate(), candidate, next, next_history)
+                })
+                .max_by_key(|branch| OrderedFloat(branch.0))
+                .unwrap();
+            prob = next;
+            history = next_history;
+            let test = Test {
+                inputs,
+                outputs: best
+                    .iter()
+                    .chain(history.memory_of(&outputs).iter())
+                    .cloned()
+                    .collect(),
+            };
+            tests.push(Example::new(test, self.normalize(branch_score, &baselines)));
+            if prob.is_solved() {
+                break;
+            }
+        }
+        let score = self.normalize(prob.evaluate(), &baselines);
+        self.playout_scores.push(score);
+        self.observer.notify(Event::PlayoutFinished {
+            score,
+            steps: tests.len(),
+        });
+        tests
+    }
+
+    /// Lets the network play the given number of steps, or until the problem is solved.
+    fn roll_out(&self, prob: &mut P, history: &mut History, steps: usize) {
+        for _ in 0..steps {
+            if prob.is_solved() {
+                break;
+            }
+            let action = history.act(&self.net, prob.get_state());
+            prob.make_step(&action);
+        }
+    }
+
+    /// Takes a list of playouts done on a problem.
+    /// Changes every choice to a test weighted by the advantage of its credit over the baseline,
+    /// the advantages are divided by the deviation of the credits.
+    pub fn gen_policy_tests(&mut self, games: Vec<(f64, Vec<Choice>)>) -> Vec<Example> {
+        let choices = games.into_iter().flat_map(|g| g.1).collect::<Vec<Choice>>();
+        let len = choices.len().max(1) as f64;
+        let mean = choices.iter().map(|c| c.credit).sum::<f64>() / len;
+        let deviation =
+            (choices.iter().map(|c| (c.credit - mean).powi(2)).sum::<f64>() / len).sqrt();
+        if deviation < 1e-12 {
+            // every choice is as good as the others, nothing to learn
+            return vec![];
+        }
+        let mut tests = vec![];
+        for choice in choices {
+            let baseline = match self.params.policy_baseline {
+                PolicyBaseline::Average => mean,
+                PolicyBaseline::Learned => self.learn_baseline(&choice.inputs, choice.credit),
+            };
+            let advantage = (choice.credit - baseline) / deviation;
+            let score = choice.credit;
+            let test = choice.into_policy_test(advantage, self.params.policy_step);
+            tests.push(Example::new(test, score));
         }
-        self.gen_tests_from_choices(results_prob)
+        tests
+    }
+
+    /// Returns the baseline's estimation of the score from the state, then moves it toward the
+    /// score obtained (normalized least mean squares).
+    fn learn_baseline(&mut self, state: &Vector<f64>, score: f64) -> f64 {
+        let features = state.iter().cloned().chain(Some(1.0)).collect::<Vec<f64>>();
+        let estimation = features
+            .iter()
+            .zip(self.baseline_weights.iter())
+            .map(|(f, w)| f * w)
+            .sum::<f64>();
+        let norm = 1.0 + features.iter().map(|f| f * f).sum::<f64>();
+        let step = self.params.baseline_rate * (score - estimation) / norm;
+        for (w, f) in self.baseline_weights.iter_mut().zip(features.iter()) {
+            *w += step * f;
+        }
+        estimation
     }
 
     /// Takes a list of playouts done on a problem.
     /// Changes the choices of the best playouts to reinforcment tests.
-    pub fn gen_tests_from_choices(&mut self, mut games: Vec<(f64, Vec<Choice>)>) -> Vec<Test> {
-        use ordered_float::OrderedFloat;
-        games.sort_by_key(|g| OrderedFloat(g.0));
-        let len = games.len();
-        let lower = (len as f64 * self.params.percent_elite) as usize;
-        let upper = (len as f64 * (1.0 - self.params.percent_elite)) as usize;
-        games
-            .into_iter()
-            .enumerate()
-            .map(|(index, (_, choices))| {
-                if index < lower {
-                    choices
-                        .into_iter()
-                        .map(|c| c.into_bad_test())
-                        .collect::<Vec<Test>>()
-                } else if index > upper {
-                    choices
-                        .into_iter()
-                        .map(|c| c.into_good_test())
-                        .collect::<Vec<Test>>()
-                } else {
-                    vec![]
-                }
-            })
-            .flatten()
+    /// When the problem gives a reward every step, the choices are sorted by their own advantage
+    /// instead of by the score of their playout.
+    pub fn gen_tests_from_choices(&mut self, games: Vec<(f64, Vec<Choice>)>) -> Vec<Example> {
+        let (bad, good) = split_choices(games, self.params.percent_elite);
+        bad.into_iter()
+            .map(|c| c.into_example(false))
+            .chain(good.into_iter().map(|c| c.into_example(true)))
             .collect()
     }
 
-    /// Asks the network to play the game.
-    /// Returns the choices made and the score obtained.
-    pub fn play_problem(&mut self, problem: P) -> (f64, Vec<Choice>) {
+    /// Asks the network to play the game, the baselines are the scores of the baseline outputs on
+    /// it.
+    /// Returns the choices made and the normalized score obtained.
+    pub fn play_problem(&mut self, problem: P, baselines: &[f64]) -> (f64, Vec<Choice>) {
         let mut prob = problem;
+        let mut history = self.new_history(&prob);
         let mut choices = vec![];
-        for _ in 0..prob.max_step().unwrap_or(MAX_GENETIC_ALG_GEN) {
-            choices.push(self.make_choice(&mut prob));
+        self.exploration.reset();
+        for _ in 0..prob.max_step().unwrap_or(self.params.max_steps) {
+            choices.push(self.make_choice(&mut prob, &mut history));
             if prob.is_solved() {
                 break;
             }
         }
-        (prob.evaluate(), choices)
+        let score = self.normalize(prob.evaluate(), baselines);
+        let scale = self.reward_scale(baselines);
+        for choice in choices.iter_mut() {
+            choice.reward = choice.reward.map(|r| r * scale);
+        }
+        credit(score, self.params.gamma, &mut choices);
+        self.playout_scores.push(score);
+        self.observer.notify(Event::PlayoutFinished {
+            score,
+            steps: choices.len(),
+        });
+        (score, choices)
     }
 
-    /// Make a choice given a problem.
-    pub fn make_choice(&mut self, prob: &mut P) -> Choice {
-        let inputs = prob.get_state();
+    /// Make a choice given a problem and what the network remembers of the previous steps.
+    /// Only the action is noisy, the memory is kept as the network gave it.
+    pub fn make_choice(&mut self, prob: &mut P, history: &mut History) -> Choice {
+        let observation = prob.get_state();
+        let inputs = history.inputs(&observation);
         let outputs = self.net.feed_forward(&inputs);
-        let choice = self.modify_outputs(&outputs);
+        let action = history.action_of(&outputs);
+        let choice = self.modify_outputs(&action);
         prob.make_step(&choice);
-        Choice::new(inputs, outputs, choice)
+        history.record(observation, &choice, &outputs);
+        let mut choice = Choice::new(inputs, action, choice);
+        choice.memory = history.memory_of(&outputs);
+        choice.reward = prob.step_reward();
+        choice
     }
 
+    /// Adds the exploration noise to the outputs of the network.
     pub fn modify_outputs(&mut self, res: &Vector<f64>) -> Vector<f64> {
-        res.iter()
-            .map(|val| {
-                let ret = val + (self.random.gen::<f64>() - 0.5) * self.coef;
-                if ret > 1.0 {
-                    1.0
-                } else if ret < 0.0 {
-                    0.0
-                } else {
-                    ret
-                }
-            })
-            .collect()
+        self.exploration.explore(res, self.coef, &mut self.random)
     }
 }
 
+/// The score compared to the scores of the baselines on the same problem, see Normalization.
+/// The ratio divides by at least floor and stays within MAX_RATIO of 1.
+fn normalize(normalization: Normalization, score: f64, baselines: &[f64], floor: f64) -> f64 {
+    match normalization {
+        Normalization::Raw => score,
+        Normalization::Ratio => {
+            let baseline = baselines[0];
+            let gain = (score - baseline) / baseline.abs().max(floor);
+            1.0 + gain.max(-MAX_RATIO).min(MAX_RATIO)
+        }
+        Normalization::Rank => {
+            let worse = baselines
+                .iter()
+                .map(|&b| match (b < score, b > score) {
+                    (true, _) => 1.0,
+                    (_, true) => 0.0,
+                    // a tie counts half
+                    _ => 0.5,
+                })
+                .sum::<f64>();
+            worse / baselines.len() as f64
+        }
+    }
+}
+
+/// The least the ratio normalization divides by : a part of the average size of the neutral
+/// scores of the problems, so that a neutral score near 0 does not make the ratios explode.
+fn baseline_floor(baselines: &[Vec<f64>]) -> f64 {
+    let neutral = baselines
+        .iter()
+        .filter_map(|b| b.first())
+        .map(|b| b.abs())
+        .collect::<Vec<f64>>();
+    let average = neutral.iter().sum::<f64>() / neutral.len().max(1) as f64;
+    (BASELINE_FLOOR * average).max(MIN_BASELINE_SCORE)
+}
+
+/// Returns the choices of the worst and of the best playouts, or when the problem gives a reward
+/// every step the worst and the best choices by their advantage.
+fn split_choices(
+    games: Vec<(f64, Vec<Choice>)>,
+    percent_elite: f64,
+) -> (Vec<Choice>, Vec<Choice>) {
+    let per_step = games
+        .iter()
+        .any(|(_, choices)| choices.iter().any(|c| c.reward.is_some()));
+    if !per_step {
+        let (bad, good) = split_elite(games, percent_elite);
+        return (
+            bad.into_iter().flatten().collect(),
+            good.into_iter().flatten().collect(),
+        );
+    }
+    let advantages = step_advantages(&games);
+    let choices = games
+        .into_iter()
+        .zip(advantages)
+        .flat_map(|((_, choices), advantages)| advantages.into_iter().zip(choices))
+        .collect();
+    split_elite(choices, percent_elite)
+}
+
+/// The credit of every choice minus the average credit of the choices made at the same step of
+/// the playouts. The returns shrink toward the end of a playout, compared between steps the first
+/// choices would always look the best.
+fn step_advantages(games: &[(f64, Vec<Choice>)]) -> Vec<Vec<f64>> {
+    let longest = games.iter().map(|g| g.1.len()).max().unwrap_or(0);
+    let mut totals = vec![(0.0, 0); longest];
+    for (_, choices) in games {
+        for (total, choice) in totals.iter_mut().zip(choices.iter()) {
+            total.0 += choice.credit;
+            total.1 += 1;
+        }
+    }
+    games
+        .iter()
+        .map(|(_, choices)| {
+            choices
+                .iter()
+                .zip(totals.iter())
+                .map(|(choice, &(sum, count))| choice.credit - sum / count as f64)
+                .collect()
+        })
+        .collect()
+}
+
+/// Sorts the items by score, returns the worst and the best percent_elite of them.
+fn split_elite<T>(mut items: Vec<(f64, T)>, percent_elite: f64) -> (Vec<T>, Vec<T>) {
+    use ordered_float::OrderedFloat;
+    items.sort_by_key(|item| OrderedFloat(item.0));
+    let len = items.len();
+    let lower = (len as f64 * percent_elite) as usize;
+    let upper = (len as f64 * (1.0 - percent_elite)) as usize;
+    let (mut bad, mut good) = (vec![], vec![]);
+    for (index, (_, item)) in items.into_iter().enumerate() {
+        if index < lower {
+            bad.push(item);
+        } else if index > upper {
+            good.push(item);
+        }
+    }
+    (bad, good)
+}
+
+/// Credits every choice with the discounted sum of the rewards from its step, or with the
+/// score of the playout when the problem gives no rewards.
+fn credit(score: f64, gamma: f64, choices: &mut [Choice]) {
+    let mut future = 0.0;
+    for choice in choices.iter_mut().rev() {
+        choice.credit = match choice.reward {
+            Some(reward) => {
+                future = reward + gamma * future;
+                future
+            }
+            None => score,
+        };
+    }
+}
+
+/// Replaces the scores by their rank, between -0.5 for the worst and 0.5 for the best, so that
+/// the evolution does not depend on the scale of the scores.
+fn centered_ranks(scores: &[f64]) -> Vec<f64> {
+    use ordered_float::OrderedFloat;
+    let mut order = (0..scores.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|&i| OrderedFloat(scores[i]));
+    let mut ranks = vec![0.0; scores.len()];
+    let last = (scores.len().max(2) - 1) as f64;
+    for (rank, &index) in order.iter().enumerate() {
+        ranks[index] = rank as f64 / last - 0.5;
+    }
+    ranks
+}
+
 impl<P: ManyStepDrawable> ReiLearn<P> {
     pub fn get_frames(&self) -> Vec<DrawInstruction> {
+        self.record_demo().0
+    }
+
+    /// Lets the network play the first validation problem.
+    /// Returns the frames of every step and the problem as it is at the end.
+    pub fn record_demo(&self) -> (Vec<DrawInstruction>, P) {
+        self.record_demo_on(self.validation_problems.first().unwrap())
+    }
+
+    /// Lets the network play the problem, see record_demo.
+    pub fn record_demo_on(&self, p: &P) -> (Vec<DrawInstruction>, P) {
         let mut frames = Vec::new();
-        let p = self.test_problems.first().unwrap();
         let mut prob = p.clone();
-        for _ in 0..p.max_step().unwrap_or(MAX_GENETIC_ALG_GEN) {
-            let inputs = prob.get_state();
-            prob.make_step(&self.net.feed_forward(&inputs));
+        let mut history = self.new_history(p);
+        for _ in 0..p.max_step().unwrap_or(self.params.max_steps) {
+            let action = history.act(&self.net, prob.get_state());
+            prob.make_step(&action);
             if prob.is_solved() {
                 break;
             }
-            prob.print_state();
-            println!("append frames : ");
             frames.append(&mut prob.get_frames());
         }
-        println!("done\n");
-        frames
+        self.observer.notify(Event::DemoRecorded {
+            frames: frames.len(),
+        });
+        (frames, prob)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Playouts of five steps rewarded 1 each, but for the third one where the first two playouts
+    /// chose well and got 3 and the others chose badly and got nothing.
+    /// The inputs of every choice are its playout and its step.
+    fn playouts() -> Vec<(f64, Vec<Choice>)> {
+        (0..4)
+            .map(|game| {
+                let mut choices = (0..5)
+                    .map(|step| {
+                        let good = game < 2;
+                        let mut choice = Choice::new(
+                            Vector::new(vec![game as f64, step as f64]),
+                            Vector::new(vec![0.5]),
+                            Vector::new(vec![if good { 1.0 } else { 0.0 }]),
+                        );
+                        choice.reward = Some(match (step, good) {
+                            (2, true) => 3.0,
+                            (2, false) => 0.0,
+                            _ => 1.0,
+                        });
+                        choice
+                    })
+                    .collect::<Vec<Choice>>();
+                credit(0.0, 0.9, &mut choices);
+                (0.0, choices)
+            })
+            .collect()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn raw_keeps_the_score() {
+        assert!(close(normalize(Normalization::Raw, -3.5, &[], 1.0), -3.5));
+    }
+
+    #[test]
+    fn ratio_of_a_negative_baseline() {
+        let ratio = |score| normalize(Normalization::Ratio, score, &[-10.0], 1.0);
+        assert!(close(ratio(-5.0), 1.5));
+        assert!(close(ratio(-20.0), 0.0));
+    }
+
+    #[test]
+    fn ratio_of_a_zero_baseline_stays_bounded() {
+        let ratio = |score, floor| normalize(Normalization::Ratio, score, &[0.0], floor);
+        assert!(close(ratio(3.0, 2.0), 2.5));
+        assert!(close(ratio(1e12, MIN_BASELINE_SCORE), 1.0 + MAX_RATIO));
+        assert!(close(ratio(-1e12, MIN_BASELINE_SCORE), 1.0 - MAX_RATIO));
+    }
+
+    #[test]
+    fn tied_baselines_count_half() {
+        assert!(close(normalize(Normalization::Ratio, 4.0, &[4.0], 1.0), 1.0));
+        let rank = |score| normalize(Normalization::Rank, score, &[1.0, 2.0, 2.0, 3.0], 1.0);
+        assert!(close(rank(2.0), 0.5));
+        assert!(close(rank(2.5), 0.75));
+        assert!(close(rank(0.0), 0.0));
+        assert!(close(rank(3.0), 0.875));
+        assert!(close(normalize(Normalization::Rank, 1.0, &[1.0; 3], 1.0), 0.5));
+    }
+
+    #[test]
+    fn rank_of_negative_baselines() {
+        let rank = |score| normalize(Normalization::Rank, score, &[-3.0, -1.0], 1.0);
+        assert!(close(rank(-2.0), 0.5));
+        assert!(close(rank(0.0), 1.0));
+    }
+
+    #[test]
+    fn floor_follows_the_neutral_scores() {
+        let floor = baseline_floor(&[vec![10.0, 50.0], vec![-30.0, 0.0]]);
+        assert!(close(floor, BASELINE_FLOOR * 20.0));
+        assert!(close(baseline_floor(&[vec![0.0], vec![0.0]]), MIN_BASELINE_SCORE));
+        assert!(close(baseline_floor(&[]), MIN_BASELINE_SCORE));
+    }
+
+    fn game_and_step(choice: &Choice) -> (usize, usize) {
+        (choice.inputs[0] as usize, choice.inputs[1] as usize)
+    }
+
+    #[test]
+    fn credit_discounts_the_rewards() {
+        let credits = playouts()[0]
+            .1
+            .iter()
+            .map(|c| c.credit)
+            .collect::<Vec<f64>>();
+        let third = 3.0 + 0.9 * 1.9;
+        let expected = [1.0 + 0.9 * (1.0 + 0.9 * third), 1.0 + 0.9 * third, third, 1.9, 1.0];
+        for (credit, expected) in credits.iter().zip(expected.iter()) {
+            assert!((credit - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn advantages_compare_the_choices_of_the_same_step() {
+        let advantages = step_advantages(&playouts());
+        assert!((advantages[0][2] - 1.5).abs() < 1e-9);
+        assert!((advantages[3][2] + 1.5).abs() < 1e-9);
+        // after the decision every playout gets the same rewards
+        assert!(advantages.iter().all(|a| a[3].abs() < 1e-9 && a[4].abs() < 1e-9));
+    }
+
+    #[test]
+    fn a_decision_in_the_middle_is_labelled_good() {
+        let (bad, good) = split_choices(playouts(), 0.2);
+        let good = good.iter().map(game_and_step).collect::<Vec<_>>();
+        let bad = bad.iter().map(game_and_step).collect::<Vec<_>>();
+        // by their return alone the first steps of every playout would be the best choices
+        assert!(good.contains(&(0, 2)) && good.contains(&(1, 2)));
+        assert!(good.iter().all(|&(game, _)| game < 2));
+        assert!(bad.contains(&(2, 2)) && bad.contains(&(3, 2)));
+        assert!(bad.iter().all(|&(game, _)| game >= 2));
+    }
+
+    #[test]
+    fn playouts_without_rewards_are_split_whole() {
+        let games = (0..10)
+            .map(|game| {
+                let choices = (0..3)
+                    .map(|step| {
+                        Choice::new(
+                            Vector::new(vec![game as f64, step as f64]),
+                            Vector::new(vec![0.5]),
+                            Vector::new(vec![0.5]),
+                        )
+                    })
+                    .collect::<Vec<Choice>>();
+                (game as f64, choices)
+            })
+            .collect();
+        let (bad, good) = split_choices(games, 0.2);
+        let bad = bad.iter().map(game_and_step).collect::<Vec<_>>();
+        let good = good.iter().map(game_and_step).collect::<Vec<_>>();
+        assert_eq!(bad, vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2)]);
+        assert_eq!(good, vec![(9, 0), (9, 1), (9, 2)]);
     }
 }
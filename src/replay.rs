@@ -0,0 +1,217 @@
+//! The replay module
+//! Keeps the examples of the past learning generations, so that the network keeps learning on
+//! the good choices it found earlier instead of only on the last generation's.
+//!
+//! ```toml
+//! [learning.replay]
+//! capacity = 5000
+//! ratio = 0.5
+//! priority = 2
+//! ```
+use crate::params::*;
+use crate::random::GenRng;
+use lmsmw::Test;
+use ordered_float::OrderedFloat;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReplaySettings {
+    /// Number of examples kept, the lowest scored ones leave first. 0 keeps nothing.
+    pub capacity: usize,
+    /// Part of the examples of a retraining taken from the buffer, in [0, 1[.
+    pub ratio: f64,
+    /// The example of rank r, from the best score, is drawn with a weight of 1 / r^priority.
+    /// 0 draws them uniformly.
+    pub priority: f64,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        ReplaySettings {
+            capacity: REPLAY_CAPACITY,
+            ratio: REPLAY_RATIO,
+            priority: REPLAY_PRIORITY,
+        }
+    }
+}
+
+impl ReplaySettings {
+    /// Number of replayed examples to add to the fresh ones to get the ratio.
+    pub fn replayed_for(&self, fresh: usize) -> usize {
+        (fresh as f64 * self.ratio / (1.0 - self.ratio)).round() as usize
+    }
+}
+
+/// A test made from a choice and the score of that choice.
+#[derive(Clone)]
+pub struct Example {
+    pub test: Test,
+    pub score: f64,
+}
+
+impl Example {
+    pub fn new(test: Test, score: f64) -> Self {
+        Example { test, score }
+    }
+}
+
+/// The best scored examples of the past generations, at most capacity of them.
+pub struct ReplayBuffer {
+    examples: Vec<Example>,
+    capacity: usize,
+}
+
+impl ReplayBuffer {
+    pub fn new(capacity: usize) -> Self {
+        ReplayBuffer {
+            examples: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.examples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.examples.is_empty()
+    }
+
+    /// Adds the examples, forgetting the lowest scored ones when the buffer is full.
+    pub fn extend<I: IntoIterator<Item = Example>>(&mut self, examples: I) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.examples.extend(examples);
+        if self.examples.len() > self.capacity {
+            self.examples
+                .sort_unstable_by_key(|e| Reverse(OrderedFloat(e.score)));
+            self.examples.truncate(self.capacity);
+        }
+    }
+
+    /// Draws count tests, with replacement, the best scored ones more often depending on the
+    /// priority.
+    pub fn sample(&self, count: usize, priority: f64, rng: &mut GenRng) -> Vec<Test> {
+        if self.examples.is_empty() {
+            return vec![];
+        }
+        // ranks rather than scores : the scores may be negative and of any scale
+        let mut order = (0..self.examples.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|&i| OrderedFloat(-self.examples[i].score));
+        let cumulated = (1..=order.len())
+            .scan(0.0, |total, rank| {
+                *total += (rank as f64).powf(-priority);
+                Some(*total)
+            })
+            .collect::<Vec<f64>>();
+        let total = *cumulated.last().unwrap();
+        (0..count)
+            .map(|_| {
+                let drawn = rng.gen::<f64>() * total;
+                // the first rank whose cumulated weight is above the drawn one
+                let rank = match cumulated
+                    .binary_search_by_key(&OrderedFloat(drawn), |&c| OrderedFloat(c))
+                {
+                    Ok(rank) => rank + 1,
+                    Err(rank) => rank,
+                }
+                .min(order.len() - 1);
+                self.examples[order[rank]].test.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random;
+    use rulinalg::vector::Vector;
+
+    /// An example whose test remembers its score.
+    fn example(score: f64) -> Example {
+        let test = Test {
+            inputs: Vector::new(vec![score]),
+            outputs: Vector::new(vec![score]),
+        };
+        Example::new(test, score)
+    }
+
+    fn scores(buffer: &ReplayBuffer) -> Vec<f64> {
+        let mut scores = buffer
+            .examples
+            .iter()
+            .map(|e| e.score)
+            .collect::<Vec<f64>>();
+        scores.sort_by_key(|&s| OrderedFloat(s));
+        scores
+    }
+
+    #[test]
+    fn buffer_keeps_the_best_examples() {
+        let mut buffer = ReplayBuffer::new(3);
+        buffer.extend(vec![5.0, -1.0, 2.0].into_iter().map(example));
+        assert_eq!(buffer.len(), 3);
+        buffer.extend(vec![3.0, -4.0, 7.0].into_iter().map(example));
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(scores(&buffer), vec![3.0, 5.0, 7.0]);
+    }
+
+    #[test]
+    fn buffer_never_exceeds_capacity() {
+        let mut buffer = ReplayBuffer::new(10);
+        for generation in 0..20 {
+            buffer.extend((0..7).map(|i| example((generation * 7 + i) as f64 % 13.0)));
+            assert!(buffer.len() <= 10);
+        }
+        assert_eq!(buffer.len(), 10);
+    }
+
+    #[test]
+    fn empty_buffer_keeps_nothing() {
+        let mut buffer = ReplayBuffer::new(0);
+        buffer.extend(vec![1.0, 2.0].into_iter().map(example));
+        assert!(buffer.is_empty());
+        assert!(buffer.sample(5, 1.0, &mut random::seeded(0)).is_empty());
+    }
+
+    #[test]
+    fn best_examples_are_drawn_more_often() {
+        let mut buffer = ReplayBuffer::new(4);
+        buffer.extend(vec![0.0, 3.0, 1.0, 2.0].into_iter().map(example));
+        let drawn = buffer.sample(4000, 1.0, &mut random::seeded(42));
+        let count = |score: f64| drawn.iter().filter(|t| t.inputs[0] == score).count();
+        assert!(count(3.0) > count(2.0));
+        assert!(count(2.0) > count(1.0));
+        assert!(count(1.0) > count(0.0));
+    }
+
+    #[test]
+    fn no_priority_draws_uniformly() {
+        let mut buffer = ReplayBuffer::new(2);
+        buffer.extend(vec![0.0, 1.0].into_iter().map(example));
+        let drawn = buffer.sample(4000, 0.0, &mut random::seeded(42));
+        let best = drawn.iter().filter(|t| t.inputs[0] == 1.0).count();
+        assert!(best > 1800 && best < 2200, "{} of 4000", best);
+    }
+
+    #[test]
+    fn replayed_examples_make_the_ratio() {
+        let replayed = |ratio: f64, fresh: usize| {
+            ReplaySettings {
+                ratio,
+                ..ReplaySettings::default()
+            }
+            .replayed_for(fresh)
+        };
+        assert_eq!(replayed(0.0, 100), 0);
+        assert_eq!(replayed(0.25, 300), 100);
+        assert_eq!(replayed(0.5, 100), 100);
+        assert_eq!(replayed(0.75, 100), 300);
+        assert_eq!(replayed(0.9, 10), 90);
+    }
+}
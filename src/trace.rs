@@ -0,0 +1,154 @@
+//! The trace module
+//! Keeps a row per generation of a genetic algorithm run so the convergence can be plotted
+//! afterwards.
+use crate::algogen::{GenResult, ParamChoice};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// What happened during one generation.
+#[derive(Debug, Clone)]
+pub struct TraceRow {
+    pub generation: usize,
+    pub stats: GenResult,
+    /// The raw outputs of the supervisor.
+    pub choice: ParamChoice,
+    /// The parameters the outputs asked for, before the sanity clamps.
+    pub requested: ParamChoice,
+    /// The parameters applied.
+    pub params: ParamChoice,
+    pub pop_size: usize,
+    pub evaluations: usize,
+}
+
+/// The whole history of a run, one row per generation.
+#[derive(Debug, Clone)]
+pub struct Trace {
+    rows: Vec<TraceRow>,
+}
+
+const CSV_HEADER: &str = "generation,max,min,q1,med,q3,global,mutrate,elite,kills,birth_rate,pop_size,evaluations,\
+                          out_global,out_mutrate,out_elite,out_kills,out_birth_rate,\
+                          req_mutrate,req_elite,req_kills,req_birth_rate";
+
+impl Trace {
+    pub fn new() -> Self {
+        Trace { rows: vec![] }
+    }
+
+    /// Adds the row for the generation that just finished.
+    pub fn push(
+        &mut self,
+        stats: GenResult,
+        choice: ParamChoice,
+        requested: ParamChoice,
+        params: ParamChoice,
+        pop_size: usize,
+        evaluations: usize,
+    ) {
+        let generation = self.rows.len();
+        self.rows.push(TraceRow {
+            generation,
+            stats,
+            choice,
+            requested,
+            params,
+            pop_size,
+            evaluations,
+        });
+    }
+
+    pub fn rows(&self) -> &Vec<TraceRow> {
+        &self.rows
+    }
+
+    /// Writes the trace as CSV with a header line.
+    pub fn to_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{}", CSV_HEADER)?;
+        for r in self.rows.iter() {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                r.generation,
+                r.stats.max,
+                r.stats.min,
+                r.stats.q1,
+                r.stats.med,
+                r.stats.q3,
+                r.params.global,
+                r.params.mutrate,
+                r.params.elite,
+                r.params.kills,
+                r.params.birth_rate,
+                r.pop_size,
+                r.evaluations,
+                r.choice.global,
+                r.choice.mutrate,
+                r.choice.elite,
+                r.choice.kills,
+                r.choice.birth_rate,
+                r.requested.mutrate,
+                r.requested.elite,
+                r.requested.kills,
+                r.requested.birth_rate
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the trace as JSON Lines, one object per generation.
+    pub fn to_jsonl<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for r in self.rows.iter() {
+            writeln!(
+                out,
+                "{{\"generation\":{},\"max\":{},\"min\":{},\"q1\":{},\"med\":{},\"q3\":{},\
+                 \"global\":{},\"mutrate\":{},\"elite\":{},\"kills\":{},\"birth_rate\":{},\
+                 \"pop_size\":{},\"evaluations\":{},\"out_global\":{},\"out_mutrate\":{},\
+                 \"out_elite\":{},\"out_kills\":{},\"out_birth_rate\":{},\"req_mutrate\":{},\
+                 \"req_elite\":{},\"req_kills\":{},\"req_birth_rate\":{}}}",
+                r.generation,
+                json_number(r.stats.max),
+                json_number(r.stats.min),
+                json_number(r.stats.q1),
+                json_number(r.stats.med),
+                json_number(r.stats.q3),
+                json_number(r.params.global),
+                json_number(r.params.mutrate),
+                json_number(r.params.elite),
+                json_number(r.params.kills),
+                json_number(r.params.birth_rate),
+                r.pop_size,
+                r.evaluations,
+                json_number(r.choice.global),
+                json_number(r.choice.mutrate),
+                json_number(r.choice.elite),
+                json_number(r.choice.kills),
+                json_number(r.choice.birth_rate),
+                json_number(r.requested.mutrate),
+                json_number(r.requested.elite),
+                json_number(r.requested.kills),
+                json_number(r.requested.birth_rate)
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn save_csv<T: AsRef<Path>>(&self, path: T) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.to_csv(&mut out)
+    }
+
+    pub fn save_jsonl<T: AsRef<Path>>(&self, path: T) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.to_jsonl(&mut out)
+    }
+}
+
+/// JSON has no NaN or infinity, they are written as null.
+fn json_number(val: f64) -> String {
+    if val.is_finite() {
+        format!("{}", val)
+    } else {
+        String::from("null")
+    }
+}